@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueHint};
+use common::prover_state::{circuit::CircuitConfig, cli::CliProverStateConfig};
+
+/// Manages the preprocessed circuits shared by the leader, workers and
+/// verifier.
+#[derive(Parser)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Command,
+
+    #[clap(flatten)]
+    pub(crate) prover_state_config: CliProverStateConfig,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Prints the effective prover configuration, merging the arguments,
+    /// environment variables, `--prover-config` file and defaults, in the
+    /// format read by `--prover-config`.
+    Config,
+    /// Generates and persists the base, monolithic, per table and verifier
+    /// circuits of the circuit configuration.
+    Build {
+        /// Regenerates the circuits even if they are already persisted.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists the persisted circuits, with their version, configuration digest
+    /// or table and size, and disk usage.
+    List,
+    /// Deletes the persisted circuits not used by the circuit configuration,
+    /// or any configuration to keep, with the current
+    /// `EVM_ARITHMETIZATION_PKG_VER`.
+    Gc {
+        /// The digest of another circuit configuration to keep, as listed by
+        /// `list`.
+        #[arg(long, value_parser = parse_config_digest)]
+        keep: Vec<CircuitConfig>,
+        /// Only logs the files which would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+        /// Allows collecting a circuits store shared between hosts, e.g. on
+        /// S3, which can't be locked against other hosts persisting circuits.
+        #[arg(long)]
+        allow_shared_store: bool,
+        /// Allows deleting the circuits of every version when
+        /// `EVM_ARITHMETIZATION_PKG_VER` is not set.
+        #[arg(long)]
+        all_versions: bool,
+    },
+    /// Proves the STARKs of the transactions of sample blocks, and recommends
+    /// circuit sizes covering the degree bits each table required.
+    Recommend {
+        /// Prover input files of the sample blocks, as written by `rpc fetch`.
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        inputs: Vec<PathBuf>,
+        /// The number of degree bits added below and above the observed
+        /// ranges.
+        #[arg(long, default_value_t = 1)]
+        headroom: usize,
+    },
+}
+
+fn parse_config_digest(digest: &str) -> Result<CircuitConfig, String> {
+    CircuitConfig::from_configuration_digest(digest)
+        .ok_or_else(|| format!("invalid circuit configuration digest: {digest}"))
+}
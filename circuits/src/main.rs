@@ -0,0 +1,42 @@
+use anyhow::Result;
+use clap::Parser;
+use cli::Command;
+use dotenvy::dotenv;
+
+mod cli;
+mod init;
+mod recommend;
+mod resources;
+
+fn main() -> Result<()> {
+    dotenv().ok();
+    init::tracing();
+
+    let args = cli::Cli::parse();
+    let config = args.prover_state_config.into_config()?;
+    let prover_state_manager = config.clone().into_prover_state_manager()?;
+
+    match args.command {
+        Command::Config => {
+            print!("{}", config.with_defaults().to_toml()?);
+            Ok(())
+        }
+        Command::Build { force } => prover_state_manager.build(force),
+        Command::List => resources::list(std::io::stdout().lock()),
+        Command::Gc {
+            mut keep,
+            dry_run,
+            allow_shared_store,
+            all_versions,
+        } => {
+            keep.push(prover_state_manager.circuit_config);
+            resources::gc(&keep, dry_run, allow_shared_store, all_versions)
+        }
+        Command::Recommend { inputs, headroom } => recommend::recommend(
+            &inputs,
+            &prover_state_manager.circuit_config,
+            headroom,
+            std::io::stdout().lock(),
+        ),
+    }
+}
@@ -0,0 +1,181 @@
+//! Recommendation of circuit sizes from the degree bits of sample
+//! transactions.
+use std::{fs::File, io::BufReader, io::Write, ops::Range, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use common::prover_state::{
+    circuit::{Circuit, CircuitConfig, NUM_TABLES},
+    ProverStateManager,
+};
+use prover::BlockProverInput;
+use tracing::{info, warn};
+
+/// The range of degree bits observed for each table.
+#[derive(Debug, Default)]
+pub(crate) struct ObservedDegreeBits {
+    /// The smallest and largest degree bits of each table, `None` until a
+    /// transaction is recorded.
+    tables: [Option<(usize, usize)>; NUM_TABLES],
+}
+
+impl ObservedDegreeBits {
+    /// Records the degree bits of the tables of a transaction.
+    pub(crate) fn record(&mut self, degree_bits: &[usize; NUM_TABLES]) {
+        for (observed, &bits) in self.tables.iter_mut().zip(degree_bits) {
+            *observed = Some(match *observed {
+                Some((min, max)) => (min.min(bits), max.max(bits)),
+                None => (bits, bits),
+            });
+        }
+    }
+
+    /// Returns the range of each table covering the observed degree bits,
+    /// widened by `headroom` bits on both sides.
+    ///
+    /// Tables without observations keep their range in `configured`.
+    pub(crate) fn recommend(&self, configured: &CircuitConfig, headroom: usize) -> CircuitConfig {
+        let mut recommended = configured.clone();
+        for (circuit, observed) in self.tables.iter().enumerate() {
+            if let Some((min, max)) = observed {
+                recommended.set_circuit_size(
+                    Circuit::from(circuit),
+                    min.saturating_sub(headroom)..max + 1 + headroom,
+                );
+            }
+        }
+        recommended
+    }
+}
+
+/// Returns the tables whose degree bits are outside the configured range,
+/// along with the degree bits and the range.
+fn out_of_range<'a>(
+    configured: &'a CircuitConfig,
+    degree_bits: &'a [usize; NUM_TABLES],
+) -> impl Iterator<Item = (Circuit, usize, &'a Range<usize>)> {
+    configured
+        .enumerate()
+        .zip(degree_bits)
+        .filter(|((_, range), bits)| !range.contains(bits))
+        .map(|((circuit, range), &bits)| (circuit, bits, range))
+}
+
+/// Generates the STARK proofs of all transactions of the blocks in `inputs`,
+/// and writes the observed degree bits of each table along with the
+/// recommended circuit sizes.
+///
+/// Warns about the transactions requiring circuit sizes outside the configured
+/// ranges.
+pub(crate) fn recommend(
+    inputs: &[PathBuf],
+    configured: &CircuitConfig,
+    headroom: usize,
+    mut w: impl Write,
+) -> Result<()> {
+    let mut observed = ObservedDegreeBits::default();
+    for path in inputs {
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        let blocks: Vec<BlockProverInput> = common::format::from_reader(BufReader::new(file))
+            .with_context(|| format!("failed to read prover input from {}", path.display()))?;
+
+        for block in blocks {
+            let block_number = block.get_block_number();
+            let txns = block.into_txn_inputs()?;
+            info!(
+                "proving the STARKs of the {} transactions of block {block_number}",
+                txns.len()
+            );
+
+            for (index, txn) in txns.into_iter().enumerate() {
+                let degree_bits =
+                    ProverStateManager::stark_degree_bits(txn).with_context(|| {
+                        format!("failed to prove transaction {index} of block {block_number}")
+                    })?;
+                for (circuit, bits, range) in out_of_range(configured, &degree_bits) {
+                    warn!(
+                        "transaction {index} of block {block_number} requires {circuit} table degree bits {bits}, outside of the configured range {}..{}",
+                        range.start, range.end
+                    );
+                }
+                observed.record(&degree_bits);
+            }
+        }
+    }
+
+    let recommended = observed.recommend(configured, headroom);
+    writeln!(
+        w,
+        "{:<14} {:>8} {:>12} {:>12}",
+        "TABLE", "OBSERVED", "CONFIGURED", "RECOMMENDED"
+    )?;
+    for ((circuit, configured_range), (observed, recommended_range)) in configured
+        .enumerate()
+        .zip(observed.tables.iter().zip(recommended.iter()))
+    {
+        let observed = match observed {
+            Some((min, max)) => format!("{min}..={max}"),
+            None => "-".to_string(),
+        };
+        writeln!(
+            w,
+            "{:<14} {:>8} {:>12} {:>12}",
+            circuit.as_str(),
+            observed,
+            format!("{}..{}", configured_range.start, configured_range.end),
+            format!("{}..{}", recommended_range.start, recommended_range.end),
+        )?;
+    }
+
+    writeln!(w)?;
+    for (circuit, range) in recommended.enumerate() {
+        writeln!(
+            w,
+            "{}=\"{}..{}\"",
+            circuit.as_env_key(),
+            range.start,
+            range.end
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recommends_observed_ranges_with_headroom() {
+        let configured = CircuitConfig::default();
+        let mut observed = ObservedDegreeBits::default();
+        observed.record(&[16, 10, 15, 14, 9, 12, 18]);
+        observed.record(&[19, 11, 17, 14, 10, 13, 21]);
+
+        let recommended = observed.recommend(&configured, 1);
+        assert_eq!(recommended[Circuit::Arithmetic], 15..21);
+        assert_eq!(recommended[Circuit::KeccakSponge], 8..12);
+        assert_eq!(recommended[Circuit::Memory], 17..23);
+
+        let recommended = observed.recommend(&configured, 0);
+        assert_eq!(recommended[Circuit::Keccak], 14..15);
+    }
+
+    #[test]
+    fn keeps_configured_ranges_without_observations() {
+        let configured = CircuitConfig::default();
+        let recommended = ObservedDegreeBits::default().recommend(&configured, 1);
+        assert_eq!(
+            recommended.get_configuration_digest(),
+            configured.get_configuration_digest()
+        );
+    }
+
+    #[test]
+    fn finds_degree_bits_out_of_range() {
+        let mut configured = CircuitConfig::default();
+        configured.set_circuit_size(Circuit::Cpu, 15..20);
+        let degree_bits = [16, 10, 20, 14, 9, 12, 18];
+        let out: Vec<_> = out_of_range(&configured, &degree_bits).collect();
+        assert_eq!(out, [(Circuit::Cpu, 20, &(15..20))]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Listing and garbage collection of the persisted circuits.
+use std::io::{self, Write};
+
+use anyhow::{bail, Context as _, Result};
+use common::{
+    proof::{evm_arithmetization_pkg_ver, UNKNOWN_PKG_VER},
+    prover_state::{
+        circuit::CircuitConfig,
+        persistence::{is_temporary_file, CircuitsDirLock, PersistedResource, ResourceKind},
+        store::circuit_store,
+    },
+};
+use tracing::info;
+
+/// A resource of the circuit store.
+struct Entry {
+    name: String,
+    /// The persisted resource, `None` if not recognized.
+    resource: Option<PersistedResource>,
+    /// The size of the resource, in bytes.
+    len: u64,
+}
+
+/// Lists the resources of the circuit store, ordered by name.
+fn entries() -> Result<Vec<Entry>> {
+    let store = circuit_store();
+    let resources = store
+        .list()
+        .with_context(|| format!("failed to list {store}"))?;
+    Ok(resources
+        .into_iter()
+        .map(|stored| Entry {
+            resource: PersistedResource::parse(&stored.name),
+            name: stored.name,
+            len: stored.len,
+        })
+        .collect())
+}
+
+/// Formats a number of bytes in a human readable way.
+fn format_bytes(len: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut len = len as f64;
+    let mut unit = 0;
+    while len >= 1024.0 && unit < UNITS.len() - 1 {
+        len /= 1024.0;
+        unit += 1;
+    }
+    format!("{len:.1} {}", UNITS[unit])
+}
+
+/// Writes the persisted resources, with their version, configuration digest
+/// or table and size, and disk usage.
+pub(crate) fn list(mut w: impl Write) -> Result<()> {
+    let entries = entries()?;
+    writeln!(
+        w,
+        "{:<12} {:<10} {:<8} {:>4} {:>10}  CONFIGURATION",
+        "KIND", "VERSION", "TABLE", "SIZE", "DISK"
+    )?;
+    for entry in &entries {
+        let disk = format_bytes(entry.len);
+        match &entry.resource {
+            Some(resource) => {
+                let (table, size) = match resource.kind {
+                    ResourceKind::Table { circuit, size } => {
+                        (circuit.as_short_str().to_string(), size.to_string())
+                    }
+                    _ => ("-".to_string(), "-".to_string()),
+                };
+                writeln!(
+                    w,
+                    "{:<12} {:<10} {:<8} {:>4} {:>10}  {}",
+                    resource.kind.to_string(),
+                    resource.pkg_ver,
+                    table,
+                    size,
+                    disk,
+                    resource.config_digest.as_deref().unwrap_or("-")
+                )?;
+            }
+            None => writeln!(
+                w,
+                "{:<12} {:<10} {:<8} {:>4} {:>10}  {}",
+                "unknown", "-", "-", "-", disk, entry.name
+            )?,
+        }
+    }
+
+    let total = entries.iter().map(|entry| entry.len).sum();
+    writeln!(
+        w,
+        "{} files, {} in {}",
+        entries.len(),
+        format_bytes(total),
+        circuit_store()
+    )?;
+    Ok(())
+}
+
+/// Deletes the persisted resources which are not used by any of the given
+/// configurations with the current `evm_arithmetization` version, along with
+/// temporary files left by interrupted writes.
+///
+/// Unrecognized files are kept.
+///
+/// A store shared between hosts is only collected if `allow_shared_store` is
+/// set, as it can't be locked against other hosts persisting circuits. If the
+/// current version is unknown, the circuits of all versions are only deleted
+/// if `all_versions` is set.
+pub(crate) fn gc(
+    keep: &[CircuitConfig],
+    dry_run: bool,
+    allow_shared_store: bool,
+    all_versions: bool,
+) -> Result<()> {
+    let store = circuit_store();
+    if store.is_shared() && !allow_shared_store {
+        bail!(
+            "{store} is shared between hosts and can't be locked against circuits being \
+             persisted meanwhile; pass --allow-shared-store once no other host persists circuits"
+        );
+    }
+    let pkg_ver = evm_arithmetization_pkg_ver();
+    if pkg_ver == UNKNOWN_PKG_VER && !all_versions {
+        bail!(
+            "EVM_ARITHMETIZATION_PKG_VER is not set, so the circuits of every version would be \
+             deleted; set it, or pass --all-versions to delete them"
+        );
+    }
+
+    // Don't delete circuits being persisted by another process.
+    let _lock = CircuitsDirLock::acquire().context("failed to lock the circuits directory")?;
+
+    let mut freed = 0;
+    for entry in entries()? {
+        let stale = match &entry.resource {
+            Some(resource) => !resource.is_used_by(&pkg_ver, keep),
+            None => is_temporary_file(&entry.name),
+        };
+        if !stale {
+            continue;
+        }
+
+        let path = circuit_store().location(&entry.name);
+        if dry_run {
+            info!(
+                "would remove {} ({})",
+                path.display(),
+                format_bytes(entry.len)
+            );
+        } else {
+            info!("removing {} ({})", path.display(), format_bytes(entry.len));
+            match circuit_store().remove(&entry.name) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                result => result.with_context(|| format!("failed to remove {}", path.display()))?,
+            }
+        }
+        freed += entry.len;
+    }
+
+    info!(
+        "{} {}",
+        if dry_run { "would free" } else { "freed" },
+        format_bytes(freed)
+    );
+    Ok(())
+}
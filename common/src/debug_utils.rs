@@ -1,12 +1,36 @@
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use serde::Serialize;
 use serde_json::Error as SerdeError;
 use thiserror::Error;
+use tracing::warn;
 
-const DEBUG_FOLDER: &str = "./debug";
+pub(crate) const DEBUG_FOLDER: &str = "./debug";
+
+/// The directory in which debug inputs are saved.
+static DEBUG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the directory in which debug inputs are saved, [`DEBUG_FOLDER`] by
+/// default.
+pub fn set_debug_dir(dir: PathBuf) {
+    if let Err(dir) = DEBUG_DIR.set(dir) {
+        if dir != debug_dir() {
+            warn!(
+                "debug directory already set to {}, ignoring {}",
+                debug_dir().display(),
+                dir.display()
+            );
+        }
+    }
+}
+
+/// Returns the directory in which debug inputs are saved.
+pub fn debug_dir() -> &'static Path {
+    DEBUG_DIR.get_or_init(|| PathBuf::from(DEBUG_FOLDER))
+}
 
 /// Ensures that the specified directory exists on the filesystem.
 ///
@@ -37,7 +61,7 @@ fn ensure_directory_exists(folder_path: &Path) -> io::Result<()> {
         Err(e) => {
             if e.kind() == io::ErrorKind::NotFound {
                 // Directory does not exist, try to create it
-                fs::create_dir(folder_path)
+                fs::create_dir_all(folder_path)
             } else {
                 // Re-throw the error if it's not a 'NotFound' error
                 Err(e)
@@ -80,10 +104,10 @@ pub fn save_inputs_to_disk<T: Serialize>(
     file_name: String,
     inputs: T,
 ) -> Result<(), SaveInputError> {
-    let debug_folder = Path::new(DEBUG_FOLDER);
+    let debug_folder = debug_dir();
     let input_file_path = debug_folder.join(file_name);
 
-    // Ensure the DEBUG_FOLDER exists
+    // Ensure the debug folder exists
     ensure_directory_exists(debug_folder)
         .map_err(|e| SaveInputError::CreateDirectoryError(debug_folder.to_path_buf(), e))?;
 
@@ -0,0 +1,257 @@
+//! Encoding of proofs and prover inputs.
+//!
+//! Besides plain JSON, values can be encoded as CBOR, optionally compressed
+//! with zstd. Binary or compressed output starts with a small header which
+//! allows readers to detect the format automatically. Input without the
+//! header is read as plain JSON, so existing files remain readable.
+use std::{
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+use clap::{Args, ValueEnum};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// The help heading for the format arguments.
+const HEADING: &str = "Output format";
+
+/// Magic bytes identifying a header-prefixed file.
+const MAGIC: &[u8; 4] = b"ZBIN";
+/// The current version of the header layout.
+const HEADER_VERSION: u8 = 1;
+/// Magic bytes, header version, encoding and compression.
+const HEADER_LEN: usize = MAGIC.len() + 3;
+/// The zstd compression level, `0` selects the zstd default.
+const ZSTD_LEVEL: i32 = 0;
+
+/// Specifies how values are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    /// Human readable JSON.
+    #[default]
+    Json = 0,
+    /// Compact binary CBOR.
+    Cbor = 1,
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Encoding::Json => write!(f, "json"),
+            Encoding::Cbor => write!(f, "cbor"),
+        }
+    }
+}
+
+/// Specifies whether encoded values are compressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    /// Do not compress.
+    #[default]
+    None = 0,
+    /// Compress with zstd.
+    Zstd = 1,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// The format in which values are written.
+///
+/// Reading never requires a [`Format`], see [`from_slice`] and
+/// [`from_reader`].
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Format {
+    /// The encoding of written proofs and prover inputs.
+    #[arg(
+        long = "output-encoding",
+        help_heading = HEADING,
+        default_value_t = Encoding::Json
+    )]
+    pub encoding: Encoding,
+    /// The compression of written proofs and prover inputs.
+    #[arg(
+        long = "output-compression",
+        help_heading = HEADING,
+        default_value_t = Compression::None
+    )]
+    pub compression: Compression,
+}
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("unsupported format header version {0}")]
+    UnsupportedVersion(u8),
+    #[error("unknown encoding tag {0}")]
+    UnknownEncoding(u8),
+    #[error("unknown compression tag {0}")]
+    UnknownCompression(u8),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("JSON error: {0}")]
+    JsonPath(#[from] serde_path_to_error::Error<serde_json::Error>),
+    #[error("CBOR serialization error: {0}")]
+    CborSerialization(#[from] ciborium::ser::Error<io::Error>),
+    #[error("CBOR deserialization error: {0}")]
+    CborDeserialization(#[from] ciborium::de::Error<io::Error>),
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl Format {
+    /// Whether the format is plain JSON, which is written without a header.
+    pub fn is_plain_json(&self) -> bool {
+        self.encoding == Encoding::Json && self.compression == Compression::None
+    }
+
+    /// The file extension conventionally used for this format.
+    pub fn file_extension(&self) -> &'static str {
+        match (self.encoding, self.compression) {
+            (Encoding::Json, Compression::None) => "json",
+            (Encoding::Json, Compression::Zstd) => "json.zst",
+            (Encoding::Cbor, Compression::None) => "cbor",
+            (Encoding::Cbor, Compression::Zstd) => "cbor.zst",
+        }
+    }
+
+    /// Serializes the value into a byte vector.
+    pub fn to_vec<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, FormatError> {
+        let mut buf = Vec::new();
+        self.to_writer(&mut buf, value)?;
+        Ok(buf)
+    }
+
+    /// Serializes the value into the given writer.
+    pub fn to_writer<T: Serialize>(
+        &self,
+        mut writer: impl Write,
+        value: &T,
+    ) -> Result<(), FormatError> {
+        if self.is_plain_json() {
+            return Ok(serde_json::to_writer(writer, value)?);
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[HEADER_VERSION, self.encoding as u8, self.compression as u8])?;
+
+        match self.compression {
+            Compression::None => self.encode(writer, value),
+            Compression::Zstd => {
+                let mut encoder = zstd::stream::Encoder::new(writer, ZSTD_LEVEL)?;
+                self.encode(&mut encoder, value)?;
+                encoder.finish()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn encode<T: Serialize>(&self, writer: impl Write, value: &T) -> Result<(), FormatError> {
+        match self.encoding {
+            Encoding::Json => Ok(serde_json::to_writer(writer, value)?),
+            Encoding::Cbor => Ok(ciborium::into_writer(value, writer)?),
+        }
+    }
+}
+
+/// Deserializes a value, detecting its format from the header.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, FormatError> {
+    if !bytes.starts_with(MAGIC) {
+        let des = &mut serde_json::Deserializer::from_slice(bytes);
+        return Ok(serde_path_to_error::deserialize(des)?);
+    }
+
+    let header = &bytes[MAGIC.len()..HEADER_LEN.min(bytes.len())];
+    let &[version, encoding, compression] = header else {
+        return Err(FormatError::IoError(io::ErrorKind::UnexpectedEof.into()));
+    };
+    if version != HEADER_VERSION {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+
+    let encoding = match encoding {
+        e if e == Encoding::Json as u8 => Encoding::Json,
+        e if e == Encoding::Cbor as u8 => Encoding::Cbor,
+        e => return Err(FormatError::UnknownEncoding(e)),
+    };
+
+    let payload = &bytes[HEADER_LEN..];
+    match compression {
+        c if c == Compression::None as u8 => decode(encoding, payload),
+        c if c == Compression::Zstd as u8 => {
+            let decoder = zstd::stream::Decoder::new(payload)?;
+            decode(encoding, decoder)
+        }
+        c => Err(FormatError::UnknownCompression(c)),
+    }
+}
+
+/// Reads the reader to the end and deserializes its contents, detecting the
+/// format from the header.
+pub fn from_reader<T: DeserializeOwned>(mut reader: impl Read) -> Result<T, FormatError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    from_slice(&bytes)
+}
+
+fn decode<T: DeserializeOwned>(encoding: Encoding, reader: impl Read) -> Result<T, FormatError> {
+    match encoding {
+        Encoding::Json => {
+            let des = &mut serde_json::Deserializer::from_reader(reader);
+            Ok(serde_path_to_error::deserialize(des)?)
+        }
+        Encoding::Cbor => Ok(ciborium::from_reader(reader)?),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn formats() -> impl Iterator<Item = Format> {
+        [Encoding::Json, Encoding::Cbor]
+            .into_iter()
+            .flat_map(|encoding| {
+                [Compression::None, Compression::Zstd]
+                    .into_iter()
+                    .map(move |compression| Format {
+                        encoding,
+                        compression,
+                    })
+            })
+    }
+
+    #[test]
+    fn it_round_trips_all_formats() {
+        let value = vec![(1u64, "one".to_string()), (2, "two".to_string())];
+        for format in formats() {
+            let bytes = format.to_vec(&value).unwrap();
+            assert_eq!(
+                from_slice::<Vec<(u64, String)>>(&bytes).unwrap(),
+                value,
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_reads_plain_json_without_header() {
+        assert_eq!(from_slice::<Vec<u64>>(b"[1,2,3]").unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn it_rejects_unknown_header_version() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([HEADER_VERSION + 1, 0, 0]);
+        assert!(matches!(
+            from_slice::<Vec<u64>>(&bytes),
+            Err(FormatError::UnsupportedVersion(_))
+        ));
+    }
+}
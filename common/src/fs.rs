@@ -5,3 +5,13 @@ pub fn generate_block_proof_file_name(directory: &Option<&str>, block_height: u6
     path.push(format!("b{}.zkproof", block_height));
     path
 }
+
+pub fn generate_block_input_file_name(
+    directory: &Option<&str>,
+    block_height: u64,
+    extension: &str,
+) -> PathBuf {
+    let mut path = PathBuf::from(directory.unwrap_or(""));
+    path.push(format!("witness_b{}.{}", block_height, extension));
+    path
+}
@@ -1,5 +1,8 @@
 pub mod block_interval;
 pub mod debug_utils;
+pub mod format;
 pub mod fs;
 pub mod parsing;
+pub mod proof;
 pub mod prover_state;
+pub mod shutdown;
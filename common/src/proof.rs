@@ -0,0 +1,332 @@
+//! Block proofs as written to disk.
+//!
+//! Proofs are wrapped in a [`ProofEnvelope`] which records the block they
+//! prove and the circuits they were generated with, so that mismatched
+//! circuits can be detected before attempting verification.
+use std::{
+    env, fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::B256;
+use evm_arithmetization::proof::PublicValues;
+use proof_gen::proof_types::GeneratedBlockProof;
+use serde::{
+    de::{Error as _, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use thiserror::Error;
+
+use crate::prover_state::circuit::CircuitConfig;
+
+/// The environment variable holding the `evm_arithmetization` version the
+/// circuits were built with.
+const PKG_VER_ENV_KEY: &str = "EVM_ARITHMETIZATION_PKG_VER";
+
+/// The `evm_arithmetization` version recorded when it is unknown.
+pub const UNKNOWN_PKG_VER: &str = "NA";
+
+/// Returns the `evm_arithmetization` version the circuits are built with, or
+/// [`UNKNOWN_PKG_VER`] if unknown.
+pub fn evm_arithmetization_pkg_ver() -> String {
+    env::var(PKG_VER_ENV_KEY).unwrap_or(UNKNOWN_PKG_VER.to_string())
+}
+
+/// Returns the `evm_arithmetization` version the circuits are built with, or
+/// `None` if unknown.
+pub fn known_evm_arithmetization_pkg_ver() -> Option<String> {
+    Some(evm_arithmetization_pkg_ver()).filter(|pkg_ver| pkg_ver != UNKNOWN_PKG_VER)
+}
+
+#[derive(Error, Debug)]
+pub enum PublicValuesError {
+    #[error("the proof of block {0} carries no public values")]
+    Empty(u64),
+}
+
+/// Decodes the public values of a block proof.
+///
+/// Dummy proofs generated in `test_only` mode carry no public values.
+pub fn public_values(proof: &GeneratedBlockProof) -> Result<PublicValues, PublicValuesError> {
+    if proof.intern.public_inputs.is_empty() {
+        return Err(PublicValuesError::Empty(proof.b_height));
+    }
+    Ok(PublicValues::from_public_inputs(
+        &proof.intern.public_inputs,
+    ))
+}
+
+/// Describes how and for which block a proof was generated.
+///
+/// The block fields are absent if the public values of the proof can't be
+/// decoded, and the circuit fields if the circuits the proof was generated
+/// with are not known to the leader, i.e. when proving on remote workers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofMetadata {
+    /// The hash of the proven block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    /// The chain the proven block belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// The state trie root of the checkpoint block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_state_trie_root: Option<B256>,
+    /// The digest of the [`CircuitConfig`] the proof was generated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_config_digest: Option<String>,
+    /// The `evm_arithmetization` version the circuits were built with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_arithmetization_pkg_ver: Option<String>,
+    /// The creation time, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum ProofMetadataError {
+    #[error("proof was generated with circuit config {proof}, expected {expected}")]
+    CircuitConfigMismatch { proof: String, expected: String },
+    #[error("proof was generated with evm_arithmetization {proof}, expected {expected}")]
+    PkgVersionMismatch { proof: String, expected: String },
+}
+
+impl ProofMetadata {
+    /// Describes the given proof, generated with the given circuits if known.
+    pub fn new(proof: &GeneratedBlockProof, circuit_config: Option<&CircuitConfig>) -> Self {
+        let public_values = public_values(proof).ok();
+
+        Self {
+            block_hash: public_values
+                .as_ref()
+                .map(|public_values| B256::from(public_values.block_hashes.cur_hash.0)),
+            chain_id: public_values
+                .as_ref()
+                .map(|public_values| public_values.block_metadata.block_chain_id.low_u64()),
+            checkpoint_state_trie_root: public_values.as_ref().map(|public_values| {
+                B256::from(public_values.extra_block_data.checkpoint_state_trie_root.0)
+            }),
+            circuit_config_digest: circuit_config
+                .map(|circuit_config| circuit_config.get_configuration_digest()),
+            evm_arithmetization_pkg_ver: circuit_config.map(|_| evm_arithmetization_pkg_ver()),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Checks that the proof was generated with the given circuits.
+    ///
+    /// The `evm_arithmetization` version is only compared if it is known
+    /// locally.
+    pub fn check_circuits(&self, circuit_config: &CircuitConfig) -> Result<(), ProofMetadataError> {
+        self.check_circuit_digest(
+            &circuit_config.get_configuration_digest(),
+            known_evm_arithmetization_pkg_ver().as_deref(),
+        )
+    }
+
+    /// Checks that the proof was generated with the circuits of the given
+    /// configuration digest and, if given, `evm_arithmetization` version.
+    /// Nothing is compared if the proof does not know its circuits, and
+    /// versions are not compared if the proof does not know its own.
+    pub fn check_circuit_digest(
+        &self,
+        circuit_config_digest: &str,
+        evm_arithmetization_pkg_ver: Option<&str>,
+    ) -> Result<(), ProofMetadataError> {
+        let Some(proof_digest) = &self.circuit_config_digest else {
+            return Ok(());
+        };
+        if proof_digest != circuit_config_digest {
+            return Err(ProofMetadataError::CircuitConfigMismatch {
+                proof: proof_digest.clone(),
+                expected: circuit_config_digest.to_string(),
+            });
+        }
+
+        if let (Some(proof), Some(expected)) = (
+            self.evm_arithmetization_pkg_ver.as_deref(),
+            evm_arithmetization_pkg_ver,
+        ) {
+            if proof != UNKNOWN_PKG_VER && proof != expected {
+                return Err(ProofMetadataError::PkgVersionMismatch {
+                    proof: proof.to_string(),
+                    expected: expected.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A block proof together with its [`ProofMetadata`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    pub metadata: ProofMetadata,
+    pub proof: GeneratedBlockProof,
+}
+
+impl ProofEnvelope {
+    /// Wraps the given proof, generated with the given circuits if known.
+    pub fn new(proof: GeneratedBlockProof, circuit_config: Option<&CircuitConfig>) -> Self {
+        Self {
+            metadata: ProofMetadata::new(&proof, circuit_config),
+            proof,
+        }
+    }
+}
+
+/// A block proof as read from disk.
+///
+/// Proofs written before the introduction of [`ProofEnvelope`] are bare
+/// [`GeneratedBlockProof`]s, and remain readable.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum BlockProofFile {
+    Envelope(ProofEnvelope),
+    Bare(GeneratedBlockProof),
+}
+
+impl<'de> Deserialize<'de> for BlockProofFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(BlockProofFileVisitor)
+    }
+}
+
+/// Tells envelopes and bare proofs apart by their fields, deserializing each
+/// field once into its type, such that errors point at the invalid field.
+struct BlockProofFileVisitor;
+
+impl<'de> Visitor<'de> for BlockProofFileVisitor {
+    type Value = BlockProofFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a proof envelope or a block proof")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut metadata, mut proof, mut b_height, mut intern) = (None, None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "metadata" => metadata = Some(map.next_value()?),
+                "proof" => proof = Some(map.next_value()?),
+                "b_height" => b_height = Some(map.next_value()?),
+                "intern" => intern = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let is_envelope = metadata.is_some() || proof.is_some();
+        let is_bare = b_height.is_some() || intern.is_some();
+        match (is_envelope, is_bare) {
+            (true, false) => Ok(BlockProofFile::Envelope(ProofEnvelope {
+                metadata: metadata.ok_or_else(|| A::Error::missing_field("metadata"))?,
+                proof: proof.ok_or_else(|| A::Error::missing_field("proof"))?,
+            })),
+            (false, true) => Ok(BlockProofFile::Bare(GeneratedBlockProof {
+                b_height: b_height.ok_or_else(|| A::Error::missing_field("b_height"))?,
+                intern: intern.ok_or_else(|| A::Error::missing_field("intern"))?,
+            })),
+            _ => Err(A::Error::custom(
+                "expected a proof envelope, with `metadata` and `proof` fields, or a block \
+                 proof, with `b_height` and `intern` fields",
+            )),
+        }
+    }
+}
+
+impl BlockProofFile {
+    /// The metadata of the proof, if it was written with an envelope.
+    pub fn metadata(&self) -> Option<&ProofMetadata> {
+        match self {
+            BlockProofFile::Envelope(envelope) => Some(&envelope.metadata),
+            BlockProofFile::Bare(_) => None,
+        }
+    }
+
+    pub fn proof(&self) -> &GeneratedBlockProof {
+        match self {
+            BlockProofFile::Envelope(envelope) => &envelope.proof,
+            BlockProofFile::Bare(proof) => proof,
+        }
+    }
+
+    pub fn into_proof(self) -> GeneratedBlockProof {
+        match self {
+            BlockProofFile::Envelope(envelope) => envelope.proof,
+            BlockProofFile::Bare(proof) => proof,
+        }
+    }
+}
+
+impl From<BlockProofFile> for GeneratedBlockProof {
+    fn from(file: BlockProofFile) -> Self {
+        file.into_proof()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_reports_the_invalid_field() {
+        let error = serde_json::from_str::<BlockProofFile>(r#"{"b_height": "19240705"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("invalid type: string \"19240705\", expected u64"));
+
+        let error = serde_json::from_str::<BlockProofFile>(
+            r#"{"metadata": {"block_hash": "0x00"}, "proof": {}}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("invalid string length"), "{error}");
+
+        let error = serde_json::from_str::<BlockProofFile>(r#"{"block": 1}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("expected a proof envelope"), "{error}");
+    }
+
+    #[test]
+    fn it_compares_known_versions_only() {
+        let metadata = |evm_arithmetization_pkg_ver: &str| ProofMetadata {
+            block_hash: None,
+            chain_id: Some(1),
+            checkpoint_state_trie_root: None,
+            circuit_config_digest: Some("digest".to_string()),
+            evm_arithmetization_pkg_ver: Some(evm_arithmetization_pkg_ver.to_string()),
+            created_at: 0,
+        };
+
+        assert!(metadata("0.4.x")
+            .check_circuit_digest("digest", Some("0.4.x"))
+            .is_ok());
+        assert!(metadata("0.3.x")
+            .check_circuit_digest("digest", Some("0.4.x"))
+            .is_err());
+        assert!(metadata(UNKNOWN_PKG_VER)
+            .check_circuit_digest("digest", Some("0.4.x"))
+            .is_ok());
+        assert!(metadata("0.3.x")
+            .check_circuit_digest("digest", None)
+            .is_ok());
+        assert!(metadata("0.4.x")
+            .check_circuit_digest("other", Some("0.4.x"))
+            .is_err());
+
+        let unknown_circuits = ProofMetadata {
+            circuit_config_digest: None,
+            evm_arithmetization_pkg_ver: None,
+            ..metadata("0.4.x")
+        };
+        assert!(unknown_circuits
+            .check_circuit_digest("other", Some("0.3.x"))
+            .is_ok());
+    }
+}
@@ -0,0 +1,124 @@
+//! A bounded cache of values which are moved out while in use.
+//!
+//! Table circuits are required by value when proving and can't be cloned.
+//! Rather than sharing them, the cache hands out an idle value
+//! ([`CheckoutCache::checkout`]) and takes it back once the proof is done
+//! ([`CheckoutCache::checkin`]), keeping the idle values within a memory
+//! budget by evicting the least recently used ones.
+use std::{hash::Hash, mem, sync::Mutex};
+
+use lru::LruCache;
+
+pub(crate) struct CheckoutCache<K: Hash + Eq, V> {
+    /// The maximum total size of the idle values, in bytes.
+    budget: usize,
+    inner: Mutex<Inner<K, V>>,
+}
+
+struct Inner<K: Hash + Eq, V> {
+    /// The idle values of each key, along with their size.
+    entries: LruCache<K, Vec<(V, usize)>>,
+    /// The total size of the idle values.
+    size: usize,
+}
+
+impl<K: Hash + Eq, V> CheckoutCache<K, V> {
+    /// Creates a cache holding at most `budget` bytes of idle values.
+    pub(crate) fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            inner: Mutex::new(Inner {
+                entries: LruCache::unbounded(),
+                size: 0,
+            }),
+        }
+    }
+
+    /// Takes an idle value of `key` out of the cache, along with its size.
+    pub(crate) fn checkout(&self, key: &K) -> Option<(V, usize)> {
+        let mut guard = self.inner.lock().expect("cache lock poisoned");
+        let inner = &mut *guard;
+
+        let values = inner.entries.get_mut(key)?;
+        let (value, size) = values.pop()?;
+        if values.is_empty() {
+            inner.entries.pop(key);
+        }
+        inner.size -= size;
+        Some((value, size))
+    }
+
+    /// Returns a value of `key` of the given size to the cache, evicting the
+    /// least recently used values exceeding the budget.
+    pub(crate) fn checkin(&self, key: K, value: V, size: usize) {
+        if size > self.budget {
+            return;
+        }
+
+        let mut evicted = Vec::new();
+        {
+            let mut guard = self.inner.lock().expect("cache lock poisoned");
+            let inner = &mut *guard;
+
+            inner
+                .entries
+                .get_or_insert_mut(key, Vec::new)
+                .push((value, size));
+            inner.size += size;
+            while inner.size > self.budget {
+                let Some((_, values)) = inner.entries.pop_lru() else {
+                    break;
+                };
+                inner.size -= values.iter().map(|(_, size)| size).sum::<usize>();
+                evicted.push(values);
+            }
+        }
+
+        // Values may be large, drop them without holding the lock.
+        mem::drop(evicted);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checkout_takes_idle_values() {
+        let cache = CheckoutCache::new(10);
+        assert_eq!(cache.checkout(&1), None);
+
+        cache.checkin(1, "a", 2);
+        cache.checkin(1, "b", 2);
+        assert_eq!(cache.checkout(&1), Some(("b", 2)));
+        assert_eq!(cache.checkout(&1), Some(("a", 2)));
+        assert_eq!(cache.checkout(&1), None);
+    }
+
+    #[test]
+    fn checkin_evicts_least_recently_used() {
+        let cache = CheckoutCache::new(10);
+        cache.checkin(1, "a", 4);
+        cache.checkin(2, "b", 4);
+
+        // Use the first key, such that the second one is evicted.
+        let (value, size) = cache.checkout(&1).unwrap();
+        cache.checkin(1, value, size);
+        cache.checkin(3, "c", 4);
+
+        assert_eq!(cache.checkout(&2), None);
+        assert_eq!(cache.checkout(&1), Some(("a", 4)));
+        assert_eq!(cache.checkout(&3), Some(("c", 4)));
+    }
+
+    #[test]
+    fn checkin_skips_values_exceeding_budget() {
+        let cache = CheckoutCache::new(10);
+        cache.checkin(1, "a", 11);
+        assert_eq!(cache.checkout(&1), None);
+
+        let cache = CheckoutCache::new(0);
+        cache.checkin(1, "a", 1);
+        assert_eq!(cache.checkout(&1), None);
+    }
+}
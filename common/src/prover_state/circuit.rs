@@ -7,17 +7,20 @@ use std::{
 
 use evm_arithmetization::{AllStark, StarkConfig};
 use proof_gen::types::AllRecursiveCircuits;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
 use crate::parsing::{parse_range_exclusive, RangeParseError};
 
 /// Number of tables defined in plonky2.
 ///
 /// TODO: This should be made public in the evm_arithmetization crate.
-pub(crate) const NUM_TABLES: usize = 7;
+pub const NUM_TABLES: usize = 7;
 
 /// New type wrapper for [`Range`] that implements [`FromStr`] and [`Display`].
 ///
-/// Useful for using in clap arguments.
+/// Useful for using in clap arguments and configuration files, where it is
+/// written as `start..end`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CircuitSize(pub Range<usize>);
 
@@ -55,9 +58,39 @@ impl FromStr for CircuitSize {
     }
 }
 
+impl Serialize for CircuitSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CircuitSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A transaction requires a table circuit size outside of the configured
+/// range.
+///
+/// It is serializable, such that workers can report it as such to the leader.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[error(
+    "a larger circuit configuration is required: the {circuit} table requires {degree_bits} degree bits, outside of the configured range {}..{}",
+    range.start,
+    range.end
+)]
+pub struct CircuitRangeError {
+    pub circuit: Circuit,
+    pub degree_bits: usize,
+    pub range: Range<usize>,
+}
+
 /// All possible plonky2 table circuits.
 #[repr(usize)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Circuit {
     Arithmetic,
     BytePacking,
@@ -126,6 +159,13 @@ impl Circuit {
             Circuit::Memory => "m",
         }
     }
+
+    /// Get the circuit from its short str literal.
+    pub fn from_short_str(s: &str) -> Option<Self> {
+        (0..NUM_TABLES)
+            .map(Circuit::from)
+            .find(|circuit| circuit.as_short_str() == s)
+    }
 }
 
 impl From<usize> for Circuit {
@@ -219,6 +259,46 @@ impl CircuitConfig {
             })
     }
 
+    /// Check that the degree bits of each table, as required to shrink a STARK
+    /// proof, are within the configured range.
+    pub fn check_degree_bits(
+        &self,
+        degree_bits: &[usize; NUM_TABLES],
+    ) -> Result<(), CircuitRangeError> {
+        match self
+            .enumerate()
+            .zip(degree_bits)
+            .find(|((_, range), degree_bits)| !range.contains(degree_bits))
+        {
+            Some(((circuit, range), &degree_bits)) => Err(CircuitRangeError {
+                circuit,
+                degree_bits,
+                range: range.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse a config from its unique string representation, as returned by
+    /// [`CircuitConfig::get_configuration_digest`].
+    pub fn from_configuration_digest(digest: &str) -> Option<Self> {
+        let parts: Vec<_> = digest.split('_').collect();
+        if parts.len() != 2 * NUM_TABLES {
+            return None;
+        }
+
+        let mut config = Self::default();
+        for (index, pair) in parts.chunks(2).enumerate() {
+            let circuit = Circuit::from(index);
+            if pair[0] != circuit.as_short_str() {
+                return None;
+            }
+            let (start, end) = pair[1].split_once('-')?;
+            config.set_circuit_size(circuit, start.parse().ok()?..end.parse().ok()?);
+        }
+        Some(config)
+    }
+
     /// Build the circuits from the current config.
     pub fn as_all_recursive_circuits(&self) -> AllRecursiveCircuits {
         AllRecursiveCircuits::new(
@@ -1,11 +1,15 @@
 //! CLI arguments for constructing a [`CircuitConfig`], which can be used to
 //! construct table circuits.
-use std::fmt::Display;
+//!
+//! [`CircuitConfig`]: super::circuit::CircuitConfig
+use std::{fmt::Display, path::PathBuf};
 
-use clap::{Args, ValueEnum};
+use clap::{Args, ValueEnum, ValueHint};
+use serde::{Deserialize, Serialize};
 
 use super::{
-    circuit::{Circuit, CircuitConfig, CircuitSize},
+    circuit::{Circuit, CircuitSize},
+    config::{CircuitSizes, ProverStateConfig},
     ProverStateManager, TableLoadStrategy,
 };
 
@@ -24,11 +28,13 @@ fn circuit_arg_desc(circuit_name: &str) -> String {
 }
 
 /// Specifies whether to persist the processed circuits.
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum CircuitPersistence {
     /// Do not persist the processed circuits.
     None,
     /// Persist the processed circuits to disk.
+    #[default]
     Disk,
 }
 
@@ -55,10 +61,45 @@ macro_rules! gen_prover_state_config {
     ($($name:ident: $circuit:expr),*) => {
         #[derive(Args, Debug)]
         pub struct CliProverStateConfig {
-            #[clap(long, help_heading = HEADING, default_value_t = CircuitPersistence::Disk)]
-            pub persistence: CircuitPersistence,
-            #[clap(long, help_heading = HEADING, default_value_t = TableLoadStrategy::OnDemand)]
-            pub load_strategy: TableLoadStrategy,
+            /// A TOML or JSON file configuring the values below. Values given
+            /// as arguments or environment variables take precedence over the
+            /// file.
+            #[clap(
+                long,
+                help_heading = HEADING,
+                env = "ZERO_BIN_PROVER_CONFIG",
+                value_hint = ValueHint::FilePath
+            )]
+            pub prover_config: Option<PathBuf>,
+            /// Whether to persist the processed circuits [default: disk].
+            #[clap(long, help_heading = HEADING)]
+            pub persistence: Option<CircuitPersistence>,
+            /// How to load the table circuits [default: on-demand].
+            #[clap(long, help_heading = HEADING)]
+            pub load_strategy: Option<TableLoadStrategy>,
+            /// The memory budget, in MiB, of the table circuits kept loaded
+            /// between proofs with the on-demand load strategy. 0 disables the
+            /// cache [default: 4096].
+            #[clap(long, help_heading = HEADING, env = "ZERO_BIN_TABLE_CACHE_SIZE")]
+            pub table_cache_size: Option<usize>,
+            /// The directory in which the processed circuits are persisted
+            /// [default: ./circuits].
+            ///
+            /// The directory may be shared and read-only, in which case
+            /// circuits missing from it are generated but not persisted.
+            #[clap(long, help_heading = HEADING, env = "ZERO_BIN_CIRCUITS_DIR")]
+            pub circuits_dir: Option<PathBuf>,
+            /// The URL of a shared store in which the circuits are persisted
+            /// instead of the circuits directory, e.g. `s3://bucket/prefix`.
+            ///
+            /// S3 stores are configured by the `AWS_*` environment variables,
+            /// e.g. `AWS_ENDPOINT` and `AWS_ALLOW_HTTP` for MinIO.
+            #[clap(long, help_heading = HEADING, env = "ZERO_BIN_CIRCUITS_STORE")]
+            pub circuits_store: Option<String>,
+            /// The directory in which the inputs of failed proofs are saved
+            /// [default: ./debug].
+            #[clap(long, help_heading = HEADING, env = "ZERO_BIN_DEBUG_DIR")]
+            pub debug_dir: Option<PathBuf>,
 
             $(
                 #[clap(
@@ -85,35 +126,44 @@ gen_prover_state_config!(
 );
 
 impl CliProverStateConfig {
-    pub fn into_circuit_config(self) -> CircuitConfig {
-        let mut config = CircuitConfig::default();
-
-        [
-            (Circuit::Arithmetic, self.arithmetic),
-            (Circuit::BytePacking, self.byte_packing),
-            (Circuit::Cpu, self.cpu),
-            (Circuit::Keccak, self.keccak),
-            (Circuit::KeccakSponge, self.keccak_sponge),
-            (Circuit::Logic, self.logic),
-            (Circuit::Memory, self.memory),
-        ]
-        .into_iter()
-        .filter_map(|(circuit, range)| range.map(|range| (circuit, range)))
-        .for_each(|(circuit, range)| config.set_circuit_size(circuit, range));
-
-        config
+    /// Returns the configuration given by the arguments, with the values not
+    /// given taken from the `--prover-config` file, if any.
+    pub fn into_config(self) -> anyhow::Result<ProverStateConfig> {
+        let file = match &self.prover_config {
+            Some(path) => ProverStateConfig::from_file(path)?,
+            None => ProverStateConfig::default(),
+        };
+        let args = ProverStateConfig {
+            persistence: self.persistence,
+            load_strategy: self.load_strategy,
+            table_cache_size: self.table_cache_size,
+            circuits_dir: self.circuits_dir,
+            circuits_store: self.circuits_store,
+            debug_dir: self.debug_dir,
+            circuits: CircuitSizes {
+                arithmetic: self.arithmetic,
+                byte_packing: self.byte_packing,
+                cpu: self.cpu,
+                keccak: self.keccak,
+                keccak_sponge: self.keccak_sponge,
+                logic: self.logic,
+                memory: self.memory,
+            },
+        };
+        Ok(args.or(file))
     }
 
-    pub fn into_prover_state_manager(self) -> ProverStateManager {
-        ProverStateManager {
-            persistence: self.persistence.with_load_strategy(self.load_strategy),
-            circuit_config: self.into_circuit_config(),
-        }
+    /// Creates the [`ProverStateManager`], setting the circuits directory or
+    /// store and the debug directory for the process.
+    pub fn into_prover_state_manager(self) -> anyhow::Result<ProverStateManager> {
+        self.into_config()?.into_prover_state_manager()
     }
 }
 
-impl From<CliProverStateConfig> for ProverStateManager {
-    fn from(config: CliProverStateConfig) -> Self {
+impl TryFrom<CliProverStateConfig> for ProverStateManager {
+    type Error = anyhow::Error;
+
+    fn try_from(config: CliProverStateConfig) -> anyhow::Result<Self> {
         config.into_prover_state_manager()
     }
 }
@@ -0,0 +1,270 @@
+//! Prover state configuration files.
+//!
+//! The prover state arguments of the leader, worker, verifier and circuits
+//! binaries can be given in a TOML or JSON file, e.g.
+//!
+//! ```toml
+//! persistence = "disk"
+//! load_strategy = "on-demand"
+//! circuits_dir = "/shared/circuits"
+//! # circuits_store = "s3://bucket/circuits"
+//!
+//! [circuits]
+//! arithmetic = "16..23"
+//! memory = "17..28"
+//! ```
+//!
+//! All values are optional. Values given as arguments or environment variables
+//! take precedence over the file, which takes precedence over the defaults.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    circuit::{Circuit, CircuitConfig, CircuitSize, NUM_TABLES},
+    cli::CircuitPersistence,
+    persistence::{set_circuits_dir, CIRCUITS_FOLDER},
+    store::{self, set_circuit_store},
+    ProverStateManager, TableLoadStrategy,
+};
+use crate::debug_utils::{set_debug_dir, DEBUG_FOLDER};
+
+/// The default memory budget, in MiB, of the table circuit cache.
+pub(crate) const DEFAULT_TABLE_CACHE_SIZE: usize = 4096;
+
+/// The prover state configuration, with `None` for the values not given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProverStateConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<CircuitPersistence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_strategy: Option<TableLoadStrategy>,
+    /// The memory budget, in MiB, of the table circuit cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_cache_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuits_dir: Option<PathBuf>,
+    /// The URL of a shared store in which the circuits are persisted instead
+    /// of `circuits_dir`, e.g. `s3://bucket/prefix`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuits_store: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub circuits: CircuitSizes,
+}
+
+/// The size of each table circuit, with `None` for the sizes not given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitSizes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arithmetic: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_packing: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keccak: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keccak_sponge: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logic: Option<CircuitSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<CircuitSize>,
+}
+
+impl CircuitSizes {
+    fn entries_mut(&mut self) -> [(Circuit, &mut Option<CircuitSize>); NUM_TABLES] {
+        [
+            (Circuit::Arithmetic, &mut self.arithmetic),
+            (Circuit::BytePacking, &mut self.byte_packing),
+            (Circuit::Cpu, &mut self.cpu),
+            (Circuit::Keccak, &mut self.keccak),
+            (Circuit::KeccakSponge, &mut self.keccak_sponge),
+            (Circuit::Logic, &mut self.logic),
+            (Circuit::Memory, &mut self.memory),
+        ]
+    }
+
+    /// Returns the sizes, with the sizes not given taken from `other`.
+    pub fn or(mut self, mut other: Self) -> Self {
+        for ((_, size), (_, other)) in self.entries_mut().into_iter().zip(other.entries_mut()) {
+            if size.is_none() {
+                *size = other.take();
+            }
+        }
+        self
+    }
+
+    /// Returns the [`CircuitConfig`] with the given sizes, and the default
+    /// sizes for the others.
+    pub fn circuit_config(mut self) -> CircuitConfig {
+        let mut config = CircuitConfig::default();
+        for (circuit, size) in self.entries_mut() {
+            if let Some(size) = size.take() {
+                config.set_circuit_size(circuit, size);
+            }
+        }
+        config
+    }
+}
+
+impl From<&CircuitConfig> for CircuitSizes {
+    fn from(config: &CircuitConfig) -> Self {
+        let mut sizes = Self::default();
+        for (circuit, size) in sizes.entries_mut() {
+            *size = Some(config[circuit].clone().into());
+        }
+        sizes
+    }
+}
+
+impl ProverStateConfig {
+    /// Reads the configuration from a TOML file, or a JSON file if `path` has
+    /// a `.json` extension.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read prover config {}", path.display()))?;
+        let config = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents)?,
+            _ => toml::from_str(&contents)?,
+        };
+        Ok(config)
+    }
+
+    /// Returns the configuration, with the values not given taken from
+    /// `other`.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            persistence: self.persistence.or(other.persistence),
+            load_strategy: self.load_strategy.or(other.load_strategy),
+            table_cache_size: self.table_cache_size.or(other.table_cache_size),
+            circuits_dir: self.circuits_dir.or(other.circuits_dir),
+            circuits_store: self.circuits_store.or(other.circuits_store),
+            debug_dir: self.debug_dir.or(other.debug_dir),
+            circuits: self.circuits.or(other.circuits),
+        }
+    }
+
+    /// Returns the configuration, with the default values for the values not
+    /// given.
+    pub fn with_defaults(self) -> Self {
+        self.or(Self {
+            persistence: Some(CircuitPersistence::default()),
+            load_strategy: Some(TableLoadStrategy::default()),
+            table_cache_size: Some(DEFAULT_TABLE_CACHE_SIZE),
+            circuits_dir: Some(CIRCUITS_FOLDER.into()),
+            circuits_store: None,
+            debug_dir: Some(DEBUG_FOLDER.into()),
+            circuits: (&CircuitConfig::default()).into(),
+        })
+    }
+
+    /// Writes the configuration as TOML, in the format read by
+    /// [`ProverStateConfig::from_file`].
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Creates the [`ProverStateManager`], setting the circuits directory or
+    /// store and the debug directory for the process.
+    pub fn into_prover_state_manager(self) -> anyhow::Result<ProverStateManager> {
+        let config = self.with_defaults();
+        set_circuits_dir(config.circuits_dir.unwrap_or_default());
+        set_debug_dir(config.debug_dir.unwrap_or_default());
+        if let Some(url) = &config.circuits_store {
+            set_circuit_store(store::open(url)?);
+        }
+
+        Ok(ProverStateManager {
+            persistence: config
+                .persistence
+                .unwrap_or_default()
+                .with_load_strategy(config.load_strategy.unwrap_or_default()),
+            table_cache_budget: config.table_cache_size.unwrap_or_default() * 1024 * 1024,
+            circuit_config: config.circuits.circuit_config(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_toml_and_json() {
+        let toml: ProverStateConfig = toml::from_str(
+            r#"
+            load_strategy = "monolithic"
+            circuits_dir = "/shared/circuits"
+
+            [circuits]
+            cpu = "15..20"
+            "#,
+        )
+        .unwrap();
+        let json: ProverStateConfig = serde_json::from_str(
+            r#"{
+                "load_strategy": "monolithic",
+                "circuits_dir": "/shared/circuits",
+                "circuits": { "cpu": "15..20" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(toml, json);
+        assert_eq!(toml.load_strategy, Some(TableLoadStrategy::Monolithic));
+        assert_eq!(toml.circuits.cpu, Some((15..20).into()));
+        assert_eq!(toml.circuits.memory, None);
+
+        assert!(toml::from_str::<ProverStateConfig>("cpu = \"15..20\"").is_err());
+    }
+
+    #[test]
+    fn values_given_take_precedence() {
+        let args = ProverStateConfig {
+            table_cache_size: Some(0),
+            circuits: CircuitSizes {
+                cpu: Some((15..20).into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let file = ProverStateConfig {
+            persistence: Some(CircuitPersistence::None),
+            table_cache_size: Some(1024),
+            circuits: CircuitSizes {
+                cpu: Some((12..25).into()),
+                logic: Some((12..20).into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let config = args.or(file).with_defaults();
+        assert_eq!(config.persistence, Some(CircuitPersistence::None));
+        assert_eq!(config.table_cache_size, Some(0));
+        assert_eq!(config.circuits_dir, Some(CIRCUITS_FOLDER.into()));
+
+        let circuit_config = config.circuits.circuit_config();
+        assert_eq!(circuit_config[Circuit::Cpu], 15..20);
+        assert_eq!(circuit_config[Circuit::Logic], 12..20);
+        assert_eq!(
+            circuit_config[Circuit::Memory],
+            CircuitConfig::default()[Circuit::Memory]
+        );
+    }
+
+    #[test]
+    fn writes_readable_toml() {
+        let config = ProverStateConfig::default().with_defaults();
+        let read: ProverStateConfig = toml::from_str(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(read, config);
+    }
+}
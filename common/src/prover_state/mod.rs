@@ -13,26 +13,39 @@
 //!   [`set_prover_state_from_config`] function.
 use std::{fmt::Display, sync::OnceLock};
 
+use anyhow::Context as _;
 use clap::ValueEnum;
 use evm_arithmetization::{
     proof::AllProof, prover::prove, AllStark, GenerationInputs, StarkConfig,
 };
 use plonky2::{
-    field::goldilocks_field::GoldilocksField, plonk::config::PoseidonGoldilocksConfig,
-    util::timing::TimingTree,
+    field::goldilocks_field::GoldilocksField,
+    plonk::config::PoseidonGoldilocksConfig,
+    util::{serialization::IoError, timing::TimingTree},
 };
-use proof_gen::{proof_types::GeneratedTxnProof, prover_state::ProverState, VerifierState};
-use tracing::info;
+use proof_gen::{
+    proof_types::GeneratedTxnProof, prover_state::ProverState, types::AllRecursiveCircuits,
+    VerifierState,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
-use self::circuit::{CircuitConfig, NUM_TABLES};
+use self::{
+    cache::CheckoutCache,
+    circuit::{Circuit, CircuitConfig, NUM_TABLES},
+};
 use crate::prover_state::persistence::{
-    BaseProverResource, DiskResource, MonolithicProverResource, RecursiveCircuitResource,
-    VerifierResource,
+    BaseProverResource, CircuitsDirLock, DiskResource, DiskResourceError, MonolithicProverResource,
+    RecursiveCircuitResource, VerifierResource,
 };
 
+mod cache;
 pub mod circuit;
 pub mod cli;
+pub mod config;
 pub mod persistence;
+pub mod store;
+pub mod verifier_data;
 
 pub(crate) type Config = PoseidonGoldilocksConfig;
 pub(crate) type Field = GoldilocksField;
@@ -45,6 +58,8 @@ pub(crate) type RecursiveCircuitsForTableSize =
         SIZE,
     >;
 
+type TableCircuitCache = CheckoutCache<(Circuit, usize), RecursiveCircuitsForTableSize>;
+
 /// The global prover state.
 ///
 /// It is specified as a `OnceLock` for the following reasons:
@@ -66,6 +81,11 @@ static P_STATE: OnceLock<ProverState> = OnceLock::new();
 /// It's specified as a `OnceLock` for the same reasons as the prover state.
 static MANAGER: OnceLock<ProverStateManager> = OnceLock::new();
 
+/// The cache of table circuits loaded on demand, keyed by circuit and size.
+///
+/// It is created on first use, with the budget of the prover state manager.
+static TABLE_CACHE: OnceLock<TableCircuitCache> = OnceLock::new();
+
 pub fn p_state() -> &'static ProverState {
     P_STATE.get().expect("Prover state is not initialized")
 }
@@ -77,7 +97,8 @@ pub fn p_manager() -> &'static ProverStateManager {
 }
 
 /// Specifies how to load the table circuits.
-#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum TableLoadStrategy {
     #[default]
     /// Load the circuit tables as needed for shrinking STARK proofs.
@@ -122,6 +143,9 @@ impl Default for CircuitPersistence {
 pub struct ProverStateManager {
     pub circuit_config: CircuitConfig,
     pub persistence: CircuitPersistence,
+    /// The memory budget, in bytes, of the table circuits kept loaded between
+    /// proofs when loading them on demand.
+    pub table_cache_budget: usize,
 }
 
 impl ProverStateManager {
@@ -129,60 +153,101 @@ impl ProverStateManager {
         match self.persistence {
             CircuitPersistence::None => self,
             CircuitPersistence::Disk(_) => Self {
-                circuit_config: self.circuit_config,
                 persistence: CircuitPersistence::Disk(load_strategy),
+                ..self
             },
         }
     }
 
+    /// Returns the cache of table circuits loaded on demand.
+    fn table_cache(&self) -> &'static TableCircuitCache {
+        TABLE_CACHE.get_or_init(|| CheckoutCache::new(self.table_cache_budget))
+    }
+
+    /// Load a table circuit at the specified size, from the table cache if
+    /// cached, returning it along with its size in bytes.
+    fn load_table_circuit(
+        &self,
+        circuit: Circuit,
+        size: usize,
+    ) -> anyhow::Result<(RecursiveCircuitsForTableSize, usize)> {
+        let key = (circuit, size);
+        if let Some(cached) = self.table_cache().checkout(&key) {
+            return Ok(cached);
+        }
+
+        let table = RecursiveCircuitResource::get(&key).map_err(|e| {
+            anyhow::Error::from(e).context(format!(
+                "Attempting to load circuit: {circuit:?} at size: {size}"
+            ))
+        })?;
+        // The size on disk approximates the size in memory. If unknown, the
+        // table is not cached.
+        let bytes = store::circuit_store()
+            .size(&RecursiveCircuitResource::name(&key))
+            .map_or(usize::MAX, |len| len as usize);
+        Ok((table, bytes))
+    }
+
     /// Load the table circuits necessary to shrink the STARK proof.
     ///
-    /// [`AllProof`] provides the necessary degree bits for each circuit via the
-    /// [`AllProof::degree_bits`] method.
+    /// The degree bits of each circuit are those of the STARK proof, as
+    /// provided by the [`AllProof::degree_bits`] method.
     /// Using this information, for each circuit, a tuple is returned,
     /// containing:
     /// 1. The loaded table circuit at the specified size.
     /// 2. An offset indicating the position of the specified size within the
     ///    configured range used when pre-generating the circuits.
+    ///
+    /// The size in bytes of each table circuit is returned alongside, to
+    /// return them to the table cache once the proof is done. If a table
+    /// circuit fails to load, those already loaded are returned to the cache.
+    #[allow(clippy::type_complexity)]
     fn load_table_circuits(
         &self,
-        config: &StarkConfig,
-        all_proof: &AllProof<Field, Config, SIZE>,
-    ) -> anyhow::Result<[(RecursiveCircuitsForTableSize, u8); NUM_TABLES]> {
-        let degrees = all_proof.degree_bits(config);
-
-        /// Given a recursive circuit index (e.g., Arithmetic / 0), return a
-        /// tuple containing the loaded table at the specified size and
-        /// its offset relative to the configured range used to pre-process the
-        /// circuits.
-        macro_rules! circuit {
-            ($circuit_index:expr) => {
-                (
-                    RecursiveCircuitResource::get(&(
-                        $circuit_index.into(),
-                        degrees[$circuit_index],
-                    ))
-                    .map_err(|e| {
-                        let circuit: $crate::prover_state::circuit::Circuit = $circuit_index.into();
-                        let size = degrees[$circuit_index];
-                        anyhow::Error::from(e).context(format!(
-                            "Attempting to load circuit: {circuit:?} at size: {size}"
-                        ))
-                    })?,
-                    (degrees[$circuit_index] - self.circuit_config[$circuit_index].start) as u8,
-                )
-            };
+        degrees: &[usize; NUM_TABLES],
+    ) -> anyhow::Result<(
+        [(RecursiveCircuitsForTableSize, u8); NUM_TABLES],
+        [usize; NUM_TABLES],
+    )> {
+        // Ensures the offsets below don't underflow, and that the tables are
+        // persisted.
+        self.circuit_config.check_degree_bits(degrees)?;
+
+        let mut loaded = Vec::with_capacity(NUM_TABLES);
+        for (index, &degree) in degrees.iter().enumerate() {
+            match self.load_table_circuit(index.into(), degree) {
+                Ok(table) => loaded.push(table),
+                Err(e) => {
+                    self.checkin_table_circuits(degrees, loaded);
+                    return Err(e);
+                }
+            }
         }
 
-        Ok([
-            circuit!(0),
-            circuit!(1),
-            circuit!(2),
-            circuit!(3),
-            circuit!(4),
-            circuit!(5),
-            circuit!(6),
-        ])
+        // Each table is paired with the offset of its size relative to the
+        // configured range used to pre-process the circuits.
+        let mut loaded = loaded.into_iter();
+        let loaded: [_; NUM_TABLES] = std::array::from_fn(|index| {
+            let (table, bytes) = loaded.next().expect("all table circuits are loaded");
+            let offset = (degrees[index] - self.circuit_config[index].start) as u8;
+            ((table, offset), bytes)
+        });
+        let bytes = loaded.each_ref().map(|(_, bytes)| *bytes);
+        Ok((loaded.map(|(circuit, _)| circuit), bytes))
+    }
+
+    /// Returns the table circuits, in circuit order, loaded at the given
+    /// degrees to the table cache, along with their size in bytes.
+    fn checkin_table_circuits(
+        &self,
+        degrees: &[usize],
+        tables: impl IntoIterator<Item = (RecursiveCircuitsForTableSize, usize)>,
+    ) {
+        for (index, (table, bytes)) in tables.into_iter().enumerate() {
+            self.table_cache()
+                .checkin((index.into(), degrees[index]), table, bytes);
+        }
     }
 
     /// Generate a transaction proof using the specified input, loading the
@@ -193,13 +258,24 @@ impl ProverStateManager {
         let all_stark = AllStark::default();
         let all_proof = prove(&all_stark, &config, input, &mut TimingTree::default(), None)?;
 
-        let table_circuits = self.load_table_circuits(&config, &all_proof)?;
+        let degrees = all_proof.degree_bits(&config);
+        let (table_circuits, table_bytes) = self.load_table_circuits(&degrees)?;
 
-        let (intern, p_vals) =
+        let result =
             p_state()
                 .state
-                .prove_root_after_initial_stark(all_proof, &table_circuits, None)?;
-
+                .prove_root_after_initial_stark(all_proof, &table_circuits, None);
+
+        // Return the table circuits to the cache for the next proofs.
+        self.checkin_table_circuits(
+            &degrees,
+            table_circuits
+                .into_iter()
+                .zip(table_bytes)
+                .map(|((table, _), bytes)| (table, bytes)),
+        );
+
+        let (intern, p_vals) = result?;
         Ok(GeneratedTxnProof { intern, p_vals })
     }
 
@@ -239,9 +315,31 @@ impl ProverStateManager {
         }
     }
 
+    /// Generate the STARK proof of a transaction without shrinking it, and
+    /// return the degree bits of each table, i.e. the size of the table
+    /// circuit required to shrink it.
+    ///
+    /// This doesn't require the prover state.
+    pub fn stark_degree_bits(input: GenerationInputs) -> anyhow::Result<[usize; NUM_TABLES]> {
+        let config = StarkConfig::standard_fast_config();
+        let all_proof: AllProof<Field, Config, SIZE> = prove(
+            &AllStark::default(),
+            &config,
+            input,
+            &mut TimingTree::default(),
+            None,
+        )?;
+        Ok(all_proof.degree_bits(&config))
+    }
+
     /// Initialize global prover state from the configuration.
+    ///
+    /// If the generated circuits can't be persisted, e.g. because the circuits
+    /// directory is read-only, the prover falls back to the in-memory
+    /// monolithic circuit.
     pub fn initialize(&self) -> anyhow::Result<()> {
         info!("initializing prover state...");
+        let mut manager = self.clone();
 
         let state = match self.persistence {
             CircuitPersistence::None => {
@@ -253,27 +351,44 @@ impl ProverStateManager {
             CircuitPersistence::Disk(strategy) => {
                 info!("attempting to load preprocessed circuits from disk...");
 
-                let disk_state = match strategy {
-                    TableLoadStrategy::OnDemand => BaseProverResource::get(&self.circuit_config),
-                    TableLoadStrategy::Monolithic => {
-                        MonolithicProverResource::get(&self.circuit_config)
-                    }
-                };
-
-                match disk_state {
+                match self.load_circuits(strategy) {
                     Ok(circuits) => {
                         info!("successfully loaded preprocessed circuits from disk");
                         ProverState { state: circuits }
                     }
-                    Err(_) => {
-                        info!("failed to load preprocessed circuits from disk. generating circuits...");
+                    Err(e) => {
+                        if matches!(e, DiskResourceError::NotFound(_)) {
+                            info!("{e}");
+                        } else {
+                            warn!("failed to load preprocessed circuits from disk: {e}");
+                        }
+
+                        // Other processes may be generating the same circuits, wait for them
+                        // to be persisted rather than generating them as well.
+                        let lock = CircuitsDirLock::acquire()
+                            .inspect_err(|e| warn!("failed to lock the circuits directory: {e}"))
+                            .ok();
+                        if let Some(Ok(circuits)) =
+                            lock.as_ref().map(|_| self.load_circuits(strategy))
+                        {
+                            info!("loaded the circuits persisted by another process");
+                            return Self::set_state(manager, ProverState { state: circuits });
+                        }
+
+                        info!("generating circuits...");
                         let all_recursive_circuits =
                             self.circuit_config.as_all_recursive_circuits();
                         info!("saving preprocessed circuits to disk");
-                        persistence::persist_all_to_disk(
+                        if let Err(e) = persistence::persist_all_to_disk(
                             &all_recursive_circuits,
                             &self.circuit_config,
-                        )?;
+                        ) {
+                            warn!(
+                                "failed to save preprocessed circuits to disk, using the monolithic circuit: {e:#}"
+                            );
+                            manager.persistence =
+                                CircuitPersistence::Disk(TableLoadStrategy::Monolithic);
+                        }
                         ProverState {
                             state: all_recursive_circuits,
                         }
@@ -282,6 +397,11 @@ impl ProverStateManager {
             }
         };
 
+        Self::set_state(manager, state)
+    }
+
+    /// Sets the global prover state and its manager.
+    fn set_state(manager: ProverStateManager, state: ProverState) -> anyhow::Result<()> {
         P_STATE.set(state).map_err(|_| {
             anyhow::Error::msg(
                 "prover state already set. check the program logic to ensure it is only set once",
@@ -289,7 +409,7 @@ impl ProverStateManager {
             .context("setting prover state")
         })?;
 
-        MANAGER.set(self.clone()).map_err(|_| {
+        MANAGER.set(manager).map_err(|_| {
             anyhow::Error::msg(
                 "prover state manager already set. check the program logic to ensure it is only set once",
             )
@@ -299,6 +419,48 @@ impl ProverStateManager {
         Ok(())
     }
 
+    /// Loads the circuits required by the load strategy from disk.
+    fn load_circuits(
+        &self,
+        strategy: TableLoadStrategy,
+    ) -> Result<AllRecursiveCircuits, DiskResourceError<IoError>> {
+        match strategy {
+            TableLoadStrategy::OnDemand => BaseProverResource::get(&self.circuit_config),
+            TableLoadStrategy::Monolithic => MonolithicProverResource::get(&self.circuit_config),
+        }
+    }
+
+    /// Generates and persists all circuits of the configuration: the base,
+    /// monolithic, per table and verifier circuits.
+    ///
+    /// Nothing is generated if all circuits are already persisted, as told by
+    /// the header and size of each resource, unless `force` is set. The
+    /// circuits directory is locked meanwhile, such that provers starting
+    /// concurrently wait for the circuits instead of generating them.
+    ///
+    /// Otherwise all circuits are generated in memory, including the table
+    /// circuits already persisted, as the upstream API only builds them at
+    /// once. Only writing the persisted table circuits whose checksum is
+    /// verified, e.g. those of a narrower range, is skipped.
+    pub fn build(&self, force: bool) -> anyhow::Result<()> {
+        let _lock = CircuitsDirLock::acquire().context("failed to lock the circuits directory")?;
+
+        if !force {
+            match persistence::check_all_on_disk(&self.circuit_config) {
+                Ok(()) => {
+                    info!("circuits are already persisted");
+                    return Ok(());
+                }
+                Err(e) => info!("{e:#}"),
+            }
+        }
+
+        info!("generating circuits...");
+        let circuits = self.circuit_config.as_all_recursive_circuits();
+        info!("saving circuits to {}", store::circuit_store());
+        persistence::persist_all_to_disk(&circuits, &self.circuit_config)
+    }
+
     /// Loads a verifier state from disk or generate it.
     pub fn verifier(&self) -> anyhow::Result<VerifierState> {
         info!("initializing verifier state...");
@@ -319,13 +481,19 @@ impl ProverStateManager {
                         info!("successfully loaded preprocessed verifier circuit from disk");
                         Ok(VerifierState { state })
                     }
-                    Err(_) => {
-                        info!("failed to load preprocessed verifier circuit from disk. generating it...");
+                    Err(e) => {
+                        if matches!(e, DiskResourceError::NotFound(_)) {
+                            info!("{e}. generating verifier circuit...");
+                        } else {
+                            warn!("failed to load preprocessed verifier circuit from disk: {e}. regenerating it...");
+                        }
                         let prover_state = self.circuit_config.as_all_recursive_circuits();
 
                         info!("saving preprocessed verifier circuit to disk");
                         let state = prover_state.final_verifier_data();
-                        VerifierResource::put(&self.circuit_config, &state)?;
+                        if let Err(e) = VerifierResource::put(&self.circuit_config, &state) {
+                            warn!("failed to save preprocessed verifier circuit to disk: {e}");
+                        }
 
                         Ok(VerifierState { state })
                     }
@@ -1,26 +1,103 @@
 use std::env;
 use std::{
     fmt::{Debug, Display},
-    fs::{self, OpenOptions},
-    io::Write,
-    path::Path,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
 };
 
+use alloy::primitives::{keccak256, B256};
+use fs4::FileExt;
 use plonky2::util::serialization::{
     Buffer, DefaultGateSerializer, DefaultGeneratorSerializer, IoError,
 };
 use proof_gen::types::{AllRecursiveCircuits, VerifierData};
 use thiserror::Error;
+use tracing::{info, warn};
 
 use super::{
     circuit::{Circuit, CircuitConfig},
+    store::{circuit_store, ResourceBytes},
     Config, RecursiveCircuitsForTableSize, SIZE,
 };
+use crate::proof::evm_arithmetization_pkg_ver;
 
-const CIRCUITS_FOLDER: &str = "./circuits";
+pub(crate) const CIRCUITS_FOLDER: &str = "./circuits";
 const PROVER_STATE_FILE_PREFIX: &str = "prover_state";
 const VERIFIER_STATE_FILE_PREFIX: &str = "verifier_state";
 
+/// Magic bytes identifying a persisted circuit resource.
+const RESOURCE_MAGIC: &[u8; 4] = b"ZBCR";
+/// The current version of the resource file layout.
+const RESOURCE_LAYOUT_VERSION: u8 = 1;
+/// The file locked while generating and persisting circuits.
+const LOCK_FILE: &str = ".lock";
+
+/// The directory in which the circuits are persisted.
+///
+/// It's specified as a `OnceLock` as it is configured once at start-up, and
+/// read wherever circuits are loaded, including from worker threads.
+static CIRCUITS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the directory in which the circuits are persisted, [`CIRCUITS_FOLDER`]
+/// by default.
+///
+/// The directory can only be set once, before any circuits are loaded.
+pub fn set_circuits_dir(dir: PathBuf) {
+    if let Err(dir) = CIRCUITS_DIR.set(dir) {
+        if dir != circuits_dir() {
+            warn!(
+                "circuits directory already set to {}, ignoring {}",
+                circuits_dir().display(),
+                dir.display()
+            );
+        }
+    }
+}
+
+/// Returns the directory in which the circuits are persisted.
+pub fn circuits_dir() -> &'static Path {
+    CIRCUITS_DIR.get_or_init(|| PathBuf::from(CIRCUITS_FOLDER))
+}
+
+/// An exclusive lock on the circuits directory.
+///
+/// It is held while generating and persisting circuits, such that concurrent
+/// processes wait for the circuits to be persisted instead of all generating
+/// them. The lock is released when dropped.
+#[derive(Debug)]
+pub struct CircuitsDirLock(File);
+
+impl CircuitsDirLock {
+    /// Blocks until the lock on the circuits directory is acquired.
+    pub fn acquire() -> io::Result<Self> {
+        fs::create_dir_all(circuits_dir())?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(circuits_dir().join(LOCK_FILE))?;
+        if FileExt::try_lock_exclusive(&file).is_err() {
+            info!(
+                "waiting for another process to persist the circuits to {}...",
+                circuits_dir().display()
+            );
+            FileExt::lock_exclusive(&file)?;
+        }
+        Ok(Self(file))
+    }
+}
+
+impl Drop for CircuitsDirLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.0);
+    }
+}
+
 fn get_serializers() -> (
     DefaultGateSerializer,
     DefaultGeneratorSerializer<Config, SIZE>,
@@ -38,6 +115,211 @@ pub(crate) enum DiskResourceError<E> {
     Serialization(E),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("{} does not exist", .0.display())]
+    NotFound(PathBuf),
+    #[error("{} is corrupt: {reason}", path.display())]
+    Corrupt { path: PathBuf, reason: String },
+    #[error("{} was written by {found}, expected {expected}", path.display())]
+    VersionMismatch {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+}
+
+/// The header written ahead of each persisted resource.
+///
+/// It consists of [`RESOURCE_MAGIC`], the [`RESOURCE_LAYOUT_VERSION`], the
+/// length-prefixed `evm_arithmetization` version, the length of the serialized
+/// resource and its keccak digest.
+#[derive(Debug, PartialEq, Eq)]
+struct ResourceHeader {
+    pkg_ver: String,
+    len: u64,
+    digest: B256,
+}
+
+impl ResourceHeader {
+    fn new(payload: &[u8]) -> Self {
+        Self {
+            pkg_ver: evm_arithmetization_pkg_ver(),
+            len: payload.len() as u64,
+            digest: keccak256(payload),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(RESOURCE_MAGIC.len() + 43 + self.pkg_ver.len());
+        bytes.extend_from_slice(RESOURCE_MAGIC);
+        bytes.push(RESOURCE_LAYOUT_VERSION);
+        bytes.extend_from_slice(&(self.pkg_ver.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(self.pkg_ver.as_bytes());
+        bytes.extend_from_slice(&self.len.to_le_bytes());
+        bytes.extend_from_slice(self.digest.as_slice());
+        bytes
+    }
+}
+
+/// The number of leading bytes read to check a persisted resource by its
+/// header, which is far shorter.
+const HEADER_READ_LEN: u64 = 1024;
+
+fn corrupt<E>(path: &Path, reason: &str) -> DiskResourceError<E> {
+    DiskResourceError::Corrupt {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Parses the header of the resource read from `path`, checking its layout and
+/// `evm_arithmetization` versions, and returns it along with the bytes
+/// following it.
+fn parse_header<'a, E>(
+    path: &Path,
+    bytes: &'a [u8],
+) -> Result<(ResourceHeader, &'a [u8]), DiskResourceError<E>> {
+    fn split(bytes: &[u8], at: usize) -> Option<(&[u8], &[u8])> {
+        (bytes.len() >= at).then(|| bytes.split_at(at))
+    }
+    let truncated = || corrupt(path, "truncated header");
+    let version_mismatch = |expected: String, found: String| -> DiskResourceError<E> {
+        DiskResourceError::VersionMismatch {
+            path: path.to_path_buf(),
+            expected,
+            found,
+        }
+    };
+
+    // Files persisted before the header was introduced have no magic bytes.
+    let Some(rest) = bytes.strip_prefix(RESOURCE_MAGIC) else {
+        return Err(version_mismatch(
+            format!("layout version {RESOURCE_LAYOUT_VERSION}"),
+            "an unversioned layout".to_string(),
+        ));
+    };
+    let (version, rest) = split(rest, 1).ok_or_else(truncated)?;
+    if version[0] != RESOURCE_LAYOUT_VERSION {
+        return Err(version_mismatch(
+            format!("layout version {RESOURCE_LAYOUT_VERSION}"),
+            format!("layout version {}", version[0]),
+        ));
+    }
+
+    let (pkg_ver_len, rest) = split(rest, 2).ok_or_else(truncated)?;
+    let pkg_ver_len = u16::from_le_bytes([pkg_ver_len[0], pkg_ver_len[1]]) as usize;
+    let (pkg_ver, rest) = split(rest, pkg_ver_len).ok_or_else(truncated)?;
+    let pkg_ver = String::from_utf8_lossy(pkg_ver);
+    let expected_pkg_ver = evm_arithmetization_pkg_ver();
+    if pkg_ver != expected_pkg_ver {
+        return Err(version_mismatch(
+            format!("evm_arithmetization {expected_pkg_ver}"),
+            format!("evm_arithmetization {pkg_ver}"),
+        ));
+    }
+
+    let (len, rest) = split(rest, 8).ok_or_else(truncated)?;
+    let len = u64::from_le_bytes(len.try_into().expect("8 bytes"));
+    let (digest, rest) = split(rest, 32).ok_or_else(truncated)?;
+    let header = ResourceHeader {
+        pkg_ver: pkg_ver.into_owned(),
+        len,
+        digest: B256::from_slice(digest),
+    };
+
+    Ok((header, rest))
+}
+
+/// Checks the header of the resource read from `path`, returning the
+/// serialized resource following it.
+fn check_resource<'a, E>(path: &Path, bytes: &'a [u8]) -> Result<&'a [u8], DiskResourceError<E>> {
+    let (header, payload) = parse_header(path, bytes)?;
+    check_payload_len(path, &header, payload.len() as u64)?;
+    if keccak256(payload) != header.digest {
+        return Err(corrupt(path, "checksum mismatch"));
+    }
+
+    Ok(payload)
+}
+
+fn check_payload_len<E>(
+    path: &Path,
+    header: &ResourceHeader,
+    payload_len: u64,
+) -> Result<(), DiskResourceError<E>> {
+    if payload_len != header.len {
+        return Err(corrupt(
+            path,
+            &format!("expected {} bytes of data, found {payload_len}", header.len),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the file is a resource left partially written by
+/// [`write_atomic`], e.g. because the process was killed.
+pub fn is_temporary_file(file_name: &str) -> bool {
+    (file_name.starts_with(PROVER_STATE_FILE_PREFIX)
+        || file_name.starts_with(VERIFIER_STATE_FILE_PREFIX))
+        && file_name.contains(".tmp-")
+}
+
+/// Reads the resource named `name` from the [`circuit_store`], including its
+/// header, along with its location.
+fn read_resource<E>(name: &str) -> Result<(PathBuf, ResourceBytes), DiskResourceError<E>> {
+    let store = circuit_store();
+    let path = store.location(name);
+    match store.read(name) {
+        Ok(bytes) => Ok((path, bytes)),
+        Err(e) => Err(store_error(e, path)),
+    }
+}
+
+/// Checks the resource named `name` in the [`circuit_store`] by its header
+/// and size only, without reading, let alone hashing, the serialized resource.
+fn check_persisted_resource<E>(name: &str) -> Result<(), DiskResourceError<E>> {
+    let store = circuit_store();
+    let path = store.location(name);
+    let size = store.size(name).map_err(|e| store_error(e, path.clone()))?;
+    let bytes = store
+        .read_range(name, 0..size.min(HEADER_READ_LEN))
+        .map_err(|e| store_error(e, path.clone()))?;
+    let (header, rest) = parse_header(&path, &bytes)?;
+    let header_len = (bytes.len() - rest.len()) as u64;
+    check_payload_len(&path, &header, size - header_len)
+}
+
+fn store_error<E>(e: io::Error, path: PathBuf) -> DiskResourceError<E> {
+    match e.kind() {
+        io::ErrorKind::NotFound => DiskResourceError::NotFound(path),
+        _ => DiskResourceError::IoError(e),
+    }
+}
+
+/// Writes `header` followed by `payload` to a temporary file next to `path`,
+/// and renames it to `path` once fully written, such that readers never
+/// observe a partially written resource.
+pub(crate) fn write_atomic(path: &Path, header: &[u8], payload: &[u8]) -> io::Result<()> {
+    static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(header)?;
+        file.write_all(payload)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
 }
 
 /// A trait for generic resources that may be written to and read from disk,
@@ -53,8 +335,8 @@ pub(crate) trait DiskResource {
     /// resource on disk.
     type PathConstrutor;
 
-    /// Returns the path to the resource on disk.
-    fn path(p: &Self::PathConstrutor) -> impl AsRef<Path>;
+    /// Returns the name of the resource in the [`circuit_store`].
+    fn name(p: &Self::PathConstrutor) -> String;
 
     /// Serializes the resource to bytes.
     fn serialize(r: &Self::Resource) -> Result<Vec<u8>, DiskResourceError<Self::Error>>;
@@ -62,31 +344,35 @@ pub(crate) trait DiskResource {
     /// Deserializes the resource from bytes.
     fn deserialize(bytes: &[u8]) -> Result<Self::Resource, DiskResourceError<Self::Error>>;
 
-    /// Reads the resource from disk and deserializes it.
+    /// Reads the resource from the store, checks its integrity and
+    /// deserializes it.
     fn get(p: &Self::PathConstrutor) -> Result<Self::Resource, DiskResourceError<Self::Error>> {
-        Self::deserialize(&fs::read(Self::path(p))?)
+        let (path, bytes) = read_resource(&Self::name(p))?;
+        Self::deserialize(check_resource(&path, &bytes)?)
+    }
+
+    /// Reads the resource from the store and checks its integrity, including
+    /// its digest, without deserializing it.
+    fn check(p: &Self::PathConstrutor) -> Result<(), DiskResourceError<Self::Error>> {
+        let (path, bytes) = read_resource(&Self::name(p))?;
+        check_resource(&path, &bytes).map(|_| ())
+    }
+
+    /// Checks that the resource is fully persisted in the store by its header
+    /// and size, which is cheap even for large resources but does not verify
+    /// their digest.
+    fn check_persisted(p: &Self::PathConstrutor) -> Result<(), DiskResourceError<Self::Error>> {
+        check_persisted_resource(&Self::name(p))
     }
 
-    /// Writes the resource to disk after serializing it.
+    /// Writes the resource to the store after serializing it.
     fn put(
         p: &Self::PathConstrutor,
         r: &Self::Resource,
     ) -> Result<(), DiskResourceError<Self::Error>> {
-        // Create the base folder if non-existent.
-        if std::fs::metadata(CIRCUITS_FOLDER).is_err() {
-            std::fs::create_dir(CIRCUITS_FOLDER).map_err(|_| {
-                DiskResourceError::IoError::<Self::Error>(std::io::Error::other(
-                    "Could not create circuits folder",
-                ))
-            })?;
-        }
-
-        Ok(OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(Self::path(p))?
-            .write_all(&Self::serialize(r)?)?)
+        let payload = Self::serialize(r)?;
+        let header = ResourceHeader::new(&payload);
+        Ok(circuit_store().write(&Self::name(p), header.to_bytes(), payload)?)
     }
 }
 
@@ -101,10 +387,9 @@ impl DiskResource for BaseProverResource {
     type Error = IoError;
     type PathConstrutor = CircuitConfig;
 
-    fn path(p: &Self::PathConstrutor) -> impl AsRef<Path> {
+    fn name(p: &Self::PathConstrutor) -> String {
         format!(
-            "{}/{}_base_{}_{}",
-            CIRCUITS_FOLDER,
+            "{}_base_{}_{}",
             PROVER_STATE_FILE_PREFIX,
             env::var("EVM_ARITHMETIZATION_PKG_VER").unwrap_or("NA".to_string()),
             p.get_configuration_digest()
@@ -137,10 +422,9 @@ impl DiskResource for MonolithicProverResource {
     type Error = IoError;
     type PathConstrutor = CircuitConfig;
 
-    fn path(p: &Self::PathConstrutor) -> impl AsRef<Path> {
+    fn name(p: &Self::PathConstrutor) -> String {
         format!(
-            "{}/{}_monolithic_{}_{}",
-            CIRCUITS_FOLDER,
+            "{}_monolithic_{}_{}",
             PROVER_STATE_FILE_PREFIX,
             env::var("EVM_ARITHMETIZATION_PKG_VER").unwrap_or("NA".to_string()),
             p.get_configuration_digest()
@@ -172,10 +456,9 @@ impl DiskResource for RecursiveCircuitResource {
     type Error = IoError;
     type PathConstrutor = (Circuit, usize);
 
-    fn path((circuit_type, size): &Self::PathConstrutor) -> impl AsRef<Path> {
+    fn name((circuit_type, size): &Self::PathConstrutor) -> String {
         format!(
-            "{}/{}_{}_{}_{}",
-            CIRCUITS_FOLDER,
+            "{}_{}_{}_{}",
             PROVER_STATE_FILE_PREFIX,
             env::var("EVM_ARITHMETIZATION_PKG_VER").unwrap_or("NA".to_string()),
             circuit_type.as_short_str(),
@@ -216,10 +499,9 @@ impl DiskResource for VerifierResource {
     type Error = IoError;
     type PathConstrutor = CircuitConfig;
 
-    fn path(p: &Self::PathConstrutor) -> impl AsRef<Path> {
+    fn name(p: &Self::PathConstrutor) -> String {
         format!(
-            "{}/{}_{}_{}",
-            CIRCUITS_FOLDER,
+            "{}_{}_{}",
             VERIFIER_STATE_FILE_PREFIX,
             env::var("EVM_ARITHMETIZATION_PKG_VER").unwrap_or("NA".to_string()),
             p.get_configuration_digest()
@@ -251,10 +533,121 @@ pub fn persist_all_to_disk(
     Ok(())
 }
 
+/// The kind of a persisted circuit resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// See [`BaseProverResource`].
+    Base,
+    /// See [`MonolithicProverResource`].
+    Monolithic,
+    /// See [`RecursiveCircuitResource`].
+    Table { circuit: Circuit, size: usize },
+    /// See [`VerifierResource`].
+    Verifier,
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceKind::Base => write!(f, "base"),
+            ResourceKind::Monolithic => write!(f, "monolithic"),
+            ResourceKind::Table { .. } => write!(f, "table"),
+            ResourceKind::Verifier => write!(f, "verifier"),
+        }
+    }
+}
+
+/// A circuit resource identified by the name of its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedResource {
+    pub kind: ResourceKind,
+    /// The `evm_arithmetization` version the resource was generated with.
+    pub pkg_ver: String,
+    /// The digest of the [`CircuitConfig`] the resource was generated from.
+    ///
+    /// `None` for table circuits, which are shared by all configurations
+    /// including their size.
+    pub config_digest: Option<String>,
+}
+
+impl PersistedResource {
+    /// Parses the name of a resource file, as created by [`DiskResource::put`].
+    pub fn parse(file_name: &str) -> Option<Self> {
+        let with_digest = |kind, rest: &str| {
+            let (pkg_ver, digest) = rest.split_once('_')?;
+            CircuitConfig::from_configuration_digest(digest)?;
+            Some(Self {
+                kind,
+                pkg_ver: pkg_ver.to_string(),
+                config_digest: Some(digest.to_string()),
+            })
+        };
+
+        if let Some(rest) = file_name.strip_prefix(&format!("{VERIFIER_STATE_FILE_PREFIX}_")) {
+            return with_digest(ResourceKind::Verifier, rest);
+        }
+        let rest = file_name.strip_prefix(&format!("{PROVER_STATE_FILE_PREFIX}_"))?;
+        if let Some(rest) = rest.strip_prefix("base_") {
+            return with_digest(ResourceKind::Base, rest);
+        }
+        if let Some(rest) = rest.strip_prefix("monolithic_") {
+            return with_digest(ResourceKind::Monolithic, rest);
+        }
+
+        let (pkg_ver, rest) = rest.split_once('_')?;
+        let (circuit, size) = rest.split_once('_')?;
+        Some(Self {
+            kind: ResourceKind::Table {
+                circuit: Circuit::from_short_str(circuit)?,
+                size: size.parse().ok()?,
+            },
+            pkg_ver: pkg_ver.to_string(),
+            config_digest: None,
+        })
+    }
+
+    /// Whether the resource is used by any of the given configurations, with
+    /// the given `evm_arithmetization` version.
+    pub fn is_used_by(&self, pkg_ver: &str, configs: &[CircuitConfig]) -> bool {
+        self.pkg_ver == pkg_ver
+            && configs.iter().any(|config| match self.kind {
+                ResourceKind::Table { circuit, size } => config[circuit].contains(&size),
+                _ => {
+                    self.config_digest.as_deref()
+                        == Some(config.get_configuration_digest().as_str())
+                }
+            })
+    }
+}
+
+/// Checks that all circuits of the configuration are fully persisted, by the
+/// header and size of each resource.
+///
+/// The digests are not verified, as that requires reading every resource in
+/// full; they are verified whenever a resource is loaded instead.
+pub fn check_all_on_disk(circuit_config: &CircuitConfig) -> anyhow::Result<()> {
+    BaseProverResource::check_persisted(circuit_config)?;
+    MonolithicProverResource::check_persisted(circuit_config)?;
+    VerifierResource::check_persisted(circuit_config)?;
+    for (circuit_type, sizes) in circuit_config.enumerate() {
+        for size in sizes.clone() {
+            RecursiveCircuitResource::check_persisted(&(circuit_type, size))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Writes the provided [`AllRecursiveCircuits`] to disk.
 ///
 /// In particular, we cover both the monolothic and base prover states, as well
 /// as the individual circuit tables.
+///
+/// A table circuit already in the store is kept if it passes
+/// [`DiskResource::check`], i.e. its header matches the current
+/// `evm_arithmetization` version and the keccak digest of its full payload
+/// matches the header. Any other table circuit, e.g. a missing, truncated or
+/// corrupted one, is rewritten.
 fn prover_to_disk(
     circuit_config: &CircuitConfig,
     circuits: &AllRecursiveCircuits,
@@ -264,12 +657,136 @@ fn prover_to_disk(
 
     // Write individual circuit tables to disk, by circuit type and size. This
     // allows us to load only the necessary tables when needed.
+    //
+    // A table circuit only depends on its circuit type and size, so the intact
+    // tables already persisted for another configuration, e.g. with a narrower
+    // range, are kept as is.
+    let mut reused = 0;
     for (circuit_type, tables) in circuits.by_table.iter().enumerate() {
         let circuit_type: Circuit = circuit_type.into();
         for (size, table) in tables.by_stark_size.iter() {
-            RecursiveCircuitResource::put(&(circuit_type, *size), table)?;
+            let key = (circuit_type, *size);
+            match RecursiveCircuitResource::check(&key) {
+                Ok(()) => {
+                    reused += 1;
+                    continue;
+                }
+                Err(DiskResourceError::NotFound(_)) => {}
+                Err(e) => info!("rewriting table circuit: {e}"),
+            }
+            RecursiveCircuitResource::put(&key, table)?;
         }
     }
+    if reused > 0 {
+        info!("kept {reused} table circuits already persisted");
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resource(payload: &[u8]) -> Vec<u8> {
+        let mut bytes = ResourceHeader::new(payload).to_bytes();
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    fn check(bytes: &[u8]) -> Result<&[u8], DiskResourceError<IoError>> {
+        check_resource(Path::new("resource"), bytes)
+    }
+
+    #[test]
+    fn accepts_intact_resource() {
+        let bytes = resource(b"circuits");
+        assert_eq!(check(&bytes).unwrap(), b"circuits");
+    }
+
+    #[test]
+    fn parses_header_ahead_of_resource() {
+        let bytes = resource(b"circuits");
+        let (header, rest) = parse_header::<IoError>(Path::new("resource"), &bytes).unwrap();
+        assert_eq!(header, ResourceHeader::new(b"circuits"));
+        assert_eq!(rest, b"circuits");
+    }
+
+    #[test]
+    fn rejects_truncated_resource() {
+        let bytes = resource(b"circuits");
+        for len in [5, bytes.len() - 1] {
+            assert!(matches!(
+                check(&bytes[..len]),
+                Err(DiskResourceError::Corrupt { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_modified_resource() {
+        let mut bytes = resource(b"circuits");
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            check(&bytes),
+            Err(DiskResourceError::Corrupt { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert!(matches!(
+            check(b"circuits"),
+            Err(DiskResourceError::VersionMismatch { .. })
+        ));
+
+        let mut bytes = resource(b"circuits");
+        bytes[RESOURCE_MAGIC.len()] += 1;
+        assert!(matches!(
+            check(&bytes),
+            Err(DiskResourceError::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_resource_file_names() {
+        let config = CircuitConfig::default();
+        let pkg_ver = evm_arithmetization_pkg_ver();
+        let digest = Some(config.get_configuration_digest());
+
+        for (name, kind) in [
+            (BaseProverResource::name(&config), ResourceKind::Base),
+            (
+                MonolithicProverResource::name(&config),
+                ResourceKind::Monolithic,
+            ),
+            (VerifierResource::name(&config), ResourceKind::Verifier),
+        ] {
+            let resource = PersistedResource::parse(&name).unwrap();
+            assert_eq!(resource.kind, kind);
+            assert_eq!(resource.pkg_ver, pkg_ver);
+            assert_eq!(resource.config_digest, digest);
+            assert!(resource.is_used_by(&pkg_ver, &[config.clone()]));
+            assert!(!resource.is_used_by("other", &[config.clone()]));
+        }
+
+        let name = RecursiveCircuitResource::name(&(Circuit::KeccakSponge, 12));
+        let resource = PersistedResource::parse(&name).unwrap();
+        assert_eq!(
+            resource.kind,
+            ResourceKind::Table {
+                circuit: Circuit::KeccakSponge,
+                size: 12
+            }
+        );
+        assert!(resource.is_used_by(&pkg_ver, &[config.clone()]));
+
+        let mut other = config.clone();
+        other.set_circuit_size(Circuit::KeccakSponge, 13..20);
+        assert!(!resource.is_used_by(&pkg_ver, &[other]));
+
+        assert_eq!(PersistedResource::parse(".lock"), None);
+        assert_eq!(PersistedResource::parse(&format!("{name}.tmp-1-0")), None);
+        assert!(is_temporary_file(&format!("{name}.tmp-1-0")));
+    }
+}
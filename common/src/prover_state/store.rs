@@ -0,0 +1,450 @@
+//! Storage backends for the persisted circuits.
+//!
+//! Circuits are persisted to the local circuits directory by default
+//! ([`LocalStore`]). A cluster can instead share a single S3-compatible bucket
+//! ([`ObjectCircuitStore`]), such that workers fetch the circuits built ahead
+//! of time rather than each generating or copying them.
+//!
+//! Resources are keyed by their file name, which records the
+//! `evm_arithmetization` version and the circuit configuration digest.
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    future::Future,
+    io::{self, Read as _, Seek as _, SeekFrom},
+    ops::{Deref, Range},
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use anyhow::{bail, Context as _};
+use bytes::Bytes;
+use futures::TryStreamExt as _;
+use memmap2::Mmap;
+use object_store::{
+    aws::AmazonS3Builder, path::Path as ObjectPath, prefix::PrefixStore, MultipartUpload,
+    ObjectStore, PutPayload,
+};
+use tracing::warn;
+
+use super::persistence::{circuits_dir, write_atomic};
+
+/// The size of the parts in which resources are uploaded to an object store.
+///
+/// S3 allows at most 10,000 parts per upload, each of at least 5 MiB except
+/// for the last one.
+const UPLOAD_PART_SIZE: usize = 64 * 1024 * 1024;
+
+/// The store in which the circuits are persisted.
+static CIRCUIT_STORE: OnceLock<Box<dyn CircuitStore>> = OnceLock::new();
+
+/// Sets the store in which the circuits are persisted, a [`LocalStore`] of the
+/// [`circuits_dir`] by default.
+pub fn set_circuit_store(store: Box<dyn CircuitStore>) {
+    if let Err(store) = CIRCUIT_STORE.set(store) {
+        if store.to_string() != circuit_store().to_string() {
+            warn!(
+                "circuit store already set to {}, ignoring {store}",
+                circuit_store()
+            );
+        }
+    }
+}
+
+/// Returns the store in which the circuits are persisted.
+pub fn circuit_store() -> &'static dyn CircuitStore {
+    CIRCUIT_STORE
+        .get_or_init(|| Box::new(LocalStore::new(circuits_dir().to_path_buf())))
+        .as_ref()
+}
+
+/// Opens the circuit store at `url`, e.g. `s3://bucket/prefix`.
+///
+/// S3 stores are configured by the usual `AWS_*` environment variables, e.g.
+/// `AWS_ENDPOINT` and `AWS_ALLOW_HTTP` for S3-compatible stores such as
+/// MinIO.
+pub fn open(url: &str) -> anyhow::Result<Box<dyn CircuitStore>> {
+    match url.split_once("://") {
+        Some(("s3" | "s3a", rest)) => {
+            let prefix = rest.split_once('/').map_or("", |(_, prefix)| prefix);
+            let s3 = AmazonS3Builder::from_env()
+                .with_url(url)
+                .build()
+                .with_context(|| format!("failed to open circuit store {url}"))?;
+            Ok(Box::new(ObjectCircuitStore::new(
+                url.trim_end_matches('/').to_string(),
+                Arc::new(PrefixStore::new(s3, prefix)),
+            )?))
+        }
+        _ => bail!("unsupported circuit store {url}, expected s3://<bucket>/<prefix>"),
+    }
+}
+
+/// The bytes of a resource read from a [`CircuitStore`].
+pub enum ResourceBytes {
+    /// The file mapped in memory, such that its pages are loaded as they are
+    /// deserialized and can be reclaimed by the OS, rather than being copied
+    /// to the heap alongside the deserialized resource.
+    Mapped(Mmap),
+    /// The resource read in full.
+    Read(Vec<u8>),
+}
+
+impl Deref for ResourceBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ResourceBytes::Mapped(mmap) => mmap,
+            ResourceBytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+/// A resource of a [`CircuitStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResource {
+    pub name: String,
+    /// The size of the resource, in bytes.
+    pub len: u64,
+}
+
+/// A store of persisted resources, keyed by name.
+///
+/// Missing resources are reported as [`io::ErrorKind::NotFound`] errors.
+pub trait CircuitStore: Display + Send + Sync {
+    /// Returns the location of the resource, for reporting.
+    fn location(&self, name: &str) -> PathBuf;
+
+    /// Reads the resource.
+    fn read(&self, name: &str) -> io::Result<ResourceBytes>;
+
+    /// Reads the bytes of the resource in `range`, which must lie within the
+    /// resource.
+    fn read_range(&self, name: &str, range: Range<u64>) -> io::Result<Vec<u8>>;
+
+    /// Writes `header` followed by `payload` as the resource, such that
+    /// readers never observe a partially written resource.
+    fn write(&self, name: &str, header: Vec<u8>, payload: Vec<u8>) -> io::Result<()>;
+
+    /// Returns the size of the resource, in bytes.
+    fn size(&self, name: &str) -> io::Result<u64>;
+
+    /// Lists the resources of the store, ordered by name.
+    fn list(&self) -> io::Result<Vec<StoredResource>>;
+
+    /// Removes the resource.
+    fn remove(&self, name: &str) -> io::Result<()>;
+
+    /// Whether the store is shared between hosts, such that the lock of the
+    /// local circuits directory does not guard it against other writers.
+    fn is_shared(&self) -> bool;
+}
+
+/// Resources stored as files of a local directory.
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl Display for LocalStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.dir.display())
+    }
+}
+
+impl CircuitStore for LocalStore {
+    fn location(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn is_shared(&self) -> bool {
+        false
+    }
+
+    /// Maps the file in memory, falling back to reading it if mapping fails,
+    /// e.g. on file systems without mmap support.
+    fn read(&self, name: &str) -> io::Result<ResourceBytes> {
+        let path = self.location(name);
+        let file = File::open(&path)?;
+        // SAFETY: this process only writes resources through `write_atomic`,
+        // which renames a new file over the old one, and `circuits gc` only
+        // unlinks files, neither of which changes a mapped file on a local
+        // file system. This relies on no other tool truncating or modifying
+        // the files in place while they are loaded, and on the file system
+        // keeping unlinked files readable while open, which network file
+        // systems may not; the mapping only lives while the resource is
+        // checked and deserialized.
+        match unsafe { Mmap::map(&file) } {
+            Ok(mmap) => Ok(ResourceBytes::Mapped(mmap)),
+            Err(e) => {
+                warn!(
+                    "failed to map {} in memory, reading it: {e}",
+                    path.display()
+                );
+                fs::read(&path).map(ResourceBytes::Read)
+            }
+        }
+    }
+
+    fn read_range(&self, name: &str, range: Range<u64>) -> io::Result<Vec<u8>> {
+        let mut file = File::open(self.location(name))?;
+        file.seek(SeekFrom::Start(range.start))?;
+        let mut bytes = vec![0; (range.end - range.start) as usize];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write(&self, name: &str, header: Vec<u8>, payload: Vec<u8>) -> io::Result<()> {
+        // Create the base folder if non-existent.
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!(
+                    "Could not create circuits folder {}: {e}",
+                    self.dir.display()
+                ),
+            )
+        })?;
+        write_atomic(&self.location(name), &header, &payload)
+    }
+
+    fn size(&self, name: &str) -> io::Result<u64> {
+        Ok(fs::metadata(self.location(name))?.len())
+    }
+
+    fn list(&self) -> io::Result<Vec<StoredResource>> {
+        let mut resources = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // Skip the lock file.
+            if !metadata.is_file() || name.starts_with('.') {
+                continue;
+            }
+            resources.push(StoredResource {
+                name,
+                len: metadata.len(),
+            });
+        }
+        resources.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(resources)
+    }
+
+    fn remove(&self, name: &str) -> io::Result<()> {
+        fs::remove_file(self.location(name))
+    }
+}
+
+/// Resources stored as objects of an object store, e.g. an S3 bucket.
+pub struct ObjectCircuitStore {
+    /// The URL of the store, for reporting.
+    url: String,
+    store: Arc<dyn ObjectStore>,
+    /// The runtime on which the requests are made, as resources are loaded
+    /// from synchronous code, possibly within another runtime. Only `None`
+    /// once dropped.
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl ObjectCircuitStore {
+    pub fn new(url: String, store: Arc<dyn ObjectStore>) -> anyhow::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("circuit-store")
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            url,
+            store,
+            runtime: Some(runtime),
+        })
+    }
+
+    /// Runs `f` with the object store on the store runtime, blocking until it
+    /// completes.
+    fn block_on<F, T>(&self, f: impl FnOnce(Arc<dyn ObjectStore>) -> F) -> io::Result<T>
+    where
+        F: Future<Output = object_store::Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let runtime = self
+            .runtime
+            .as_ref()
+            .expect("runtime is only taken on drop");
+        let task = runtime.spawn(f(self.store.clone()));
+        futures::executor::block_on(task)
+            .map_err(io::Error::other)?
+            .map_err(io::Error::from)
+    }
+}
+
+impl Drop for ObjectCircuitStore {
+    fn drop(&mut self) {
+        // Dropping a runtime waits for its tasks, which panics within another
+        // runtime.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl Display for ObjectCircuitStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+impl CircuitStore for ObjectCircuitStore {
+    fn location(&self, name: &str) -> PathBuf {
+        PathBuf::from(format!("{}/{name}", self.url))
+    }
+
+    fn is_shared(&self) -> bool {
+        true
+    }
+
+    fn read(&self, name: &str) -> io::Result<ResourceBytes> {
+        let path = ObjectPath::from(name);
+        let bytes = self.block_on(|store| async move { store.get(&path).await?.bytes().await })?;
+        Ok(ResourceBytes::Read(bytes.into()))
+    }
+
+    fn read_range(&self, name: &str, range: Range<u64>) -> io::Result<Vec<u8>> {
+        let path = ObjectPath::from(name);
+        let range = range.start as usize..range.end as usize;
+        let bytes = self.block_on(|store| async move { store.get_range(&path, range).await })?;
+        Ok(bytes.into())
+    }
+
+    /// Uploads the resource in parts, as it may exceed the maximum size of a
+    /// single upload. The object only becomes visible once all parts are
+    /// uploaded.
+    fn write(&self, name: &str, header: Vec<u8>, payload: Vec<u8>) -> io::Result<()> {
+        let path = ObjectPath::from(name);
+        let header = Bytes::from(header);
+        let payload = Bytes::from(payload);
+        self.block_on(|store| async move {
+            let mut upload = store.put_multipart(&path).await?;
+            let result = upload_parts(upload.as_mut(), header, payload).await;
+            if result.is_err() {
+                let _ = upload.abort().await;
+            }
+            result
+        })
+    }
+
+    fn size(&self, name: &str) -> io::Result<u64> {
+        let path = ObjectPath::from(name);
+        let meta = self.block_on(|store| async move { store.head(&path).await })?;
+        Ok(meta.size as u64)
+    }
+
+    fn list(&self) -> io::Result<Vec<StoredResource>> {
+        let objects =
+            self.block_on(|store| async move { store.list(None).try_collect::<Vec<_>>().await })?;
+        let mut resources: Vec<_> = objects
+            .into_iter()
+            .map(|meta| StoredResource {
+                name: meta.location.to_string(),
+                len: meta.size as u64,
+            })
+            .collect();
+        resources.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(resources)
+    }
+
+    fn remove(&self, name: &str) -> io::Result<()> {
+        let path = ObjectPath::from(name);
+        self.block_on(|store| async move { store.delete(&path).await })
+    }
+}
+
+/// Uploads `header` followed by `payload` in parts of [`UPLOAD_PART_SIZE`],
+/// and completes the upload.
+async fn upload_parts(
+    upload: &mut dyn MultipartUpload,
+    header: Bytes,
+    payload: Bytes,
+) -> object_store::Result<()> {
+    let mut parts = payload
+        .chunks(UPLOAD_PART_SIZE)
+        .map(|chunk| payload.slice_ref(chunk));
+    let first = PutPayload::from_iter(std::iter::once(header).chain(parts.next()));
+    upload.put_part(first).await?;
+    for part in parts {
+        upload.put_part(part.into()).await?;
+    }
+    upload.complete().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn round_trip(store: &dyn CircuitStore) {
+        let name = format!("prover_state_test_{}", std::process::id());
+        assert_eq!(
+            store.read(&name).err().map(|e| e.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+
+        store
+            .write(&name, b"header".to_vec(), b"circuits".to_vec())
+            .unwrap();
+        assert_eq!(&*store.read(&name).unwrap(), b"headercircuits");
+        assert_eq!(store.read_range(&name, 2..8).unwrap(), b"aderci");
+        assert_eq!(store.size(&name).unwrap(), 14);
+        assert!(store.list().unwrap().contains(&StoredResource {
+            name: name.clone(),
+            len: 14
+        }));
+
+        store.remove(&name).unwrap();
+        assert_eq!(
+            store.size(&name).err().map(|e| e.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+    }
+
+    #[test]
+    fn local_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("zero_bin_store_{}", std::process::id()));
+        let store = LocalStore::new(dir.clone());
+        round_trip(&store);
+
+        let name = "prover_state_mapped";
+        store.write(name, Vec::new(), b"circuits".to_vec()).unwrap();
+        assert!(matches!(store.read(name), Ok(ResourceBytes::Mapped(_))));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn object_store_round_trip() {
+        let store =
+            ObjectCircuitStore::new("memory://circuits".to_string(), Arc::new(InMemory::new()))
+                .unwrap();
+        round_trip(&store);
+    }
+
+    /// Runs against the S3-compatible store given by
+    /// `ZERO_BIN_TEST_CIRCUIT_STORE`, e.g. a local MinIO server, if set.
+    #[test]
+    fn s3_store_round_trip() {
+        let Ok(url) = std::env::var("ZERO_BIN_TEST_CIRCUIT_STORE") else {
+            return;
+        };
+        round_trip(open(&url).unwrap().as_ref());
+    }
+}
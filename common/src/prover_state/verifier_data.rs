@@ -0,0 +1,132 @@
+//! Standalone verifier data artifacts.
+//!
+//! The final [`VerifierData`] can be exported to a single file, allowing
+//! verification hosts to load it directly instead of deriving it from a
+//! [`CircuitConfig`] and the preprocessed circuits.
+//!
+//! The file starts with a magic string, a layout version and a length-prefixed
+//! JSON [`VerifierDataHeader`], followed by the serialized verifier data. The
+//! header records the keccak digest of the verifier data, which is checked on
+//! load.
+use std::{
+    fs::{self, File},
+    io::Read as _,
+    path::Path,
+};
+
+use alloy::primitives::{keccak256, B256};
+use anyhow::{bail, ensure, Context as _};
+use proof_gen::{types::VerifierData, VerifierState};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    circuit::CircuitConfig,
+    persistence::{DiskResource, VerifierResource},
+};
+use crate::proof::evm_arithmetization_pkg_ver;
+
+/// Magic bytes identifying a verifier data file.
+const MAGIC: &[u8; 4] = b"ZBVD";
+/// The current version of the file layout.
+const VERSION: u8 = 1;
+
+/// Describes the verifier data stored in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierDataHeader {
+    /// The digest of the [`CircuitConfig`] the verifier data was derived
+    /// from.
+    pub circuit_config_digest: String,
+    /// The `evm_arithmetization` version the circuits were built with.
+    pub evm_arithmetization_pkg_ver: String,
+    /// The keccak digest of the serialized verifier data.
+    pub digest: B256,
+}
+
+/// Writes the verifier data, derived from the given circuits, to `path`.
+///
+/// Returns the written header.
+pub fn export(
+    path: &Path,
+    verifier_data: &VerifierData,
+    circuit_config: &CircuitConfig,
+) -> anyhow::Result<VerifierDataHeader> {
+    let payload = VerifierResource::serialize(verifier_data)
+        .map_err(|e| anyhow::anyhow!("failed to serialize verifier data: {e}"))?;
+    let header = VerifierDataHeader {
+        circuit_config_digest: circuit_config.get_configuration_digest(),
+        evm_arithmetization_pkg_ver: evm_arithmetization_pkg_ver(),
+        digest: keccak256(&payload),
+    };
+    let header_bytes = serde_json::to_vec(&header)?;
+
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 5 + header_bytes.len() + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&u32::try_from(header_bytes.len())?.to_le_bytes());
+    bytes.extend_from_slice(&header_bytes);
+    bytes.extend_from_slice(&payload);
+
+    fs::write(path, bytes)
+        .with_context(|| format!("failed to write verifier data to {}", path.display()))?;
+    Ok(header)
+}
+
+/// The length of the magic bytes, version and header length preceding the
+/// header.
+const PREFIX_LEN: usize = MAGIC.len() + 5;
+
+/// Checks the magic bytes and version of the file starting with `bytes`, and
+/// returns the length of its header and the bytes following the prefix.
+fn parse_prefix<'a>(bytes: &'a [u8], path: &Path) -> anyhow::Result<(usize, &'a [u8])> {
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        bail!("{} is not a verifier data file", path.display());
+    };
+    let Some((&version, rest)) = rest.split_first() else {
+        bail!("{} is truncated", path.display());
+    };
+    ensure!(
+        version == VERSION,
+        "unsupported verifier data file version {version}"
+    );
+    ensure!(rest.len() >= 4, "{} is truncated", path.display());
+    let (header_len, rest) = rest.split_at(4);
+    Ok((u32::from_le_bytes(header_len.try_into()?) as usize, rest))
+}
+
+/// Reads the header of the verifier data file at `path`, without reading the
+/// verifier data itself.
+pub fn read_header(path: &Path) -> anyhow::Result<VerifierDataHeader> {
+    let mut file = File::open(path)
+        .with_context(|| format!("failed to read verifier data from {}", path.display()))?;
+    let mut prefix = [0; PREFIX_LEN];
+    file.read_exact(&mut prefix)
+        .with_context(|| format!("{} is truncated", path.display()))?;
+    let (header_len, _) = parse_prefix(&prefix, path)?;
+
+    let mut header = vec![0; header_len];
+    file.read_exact(&mut header)
+        .with_context(|| format!("{} is truncated", path.display()))?;
+    Ok(serde_json::from_slice(&header)?)
+}
+
+/// Reads the verifier data from `path`, checking its digest.
+pub fn import(path: &Path) -> anyhow::Result<(VerifierDataHeader, VerifierState)> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read verifier data from {}", path.display()))?;
+
+    let (header_len, rest) = parse_prefix(&bytes, path)?;
+    ensure!(rest.len() >= header_len, "{} is truncated", path.display());
+    let (header, payload) = rest.split_at(header_len);
+
+    let header: VerifierDataHeader = serde_json::from_slice(header)?;
+    let digest = keccak256(payload);
+    ensure!(
+        digest == header.digest,
+        "verifier data digest {digest} does not match the recorded digest {}",
+        header.digest
+    );
+
+    let state = VerifierResource::deserialize(payload)
+        .map_err(|e| anyhow::anyhow!("failed to deserialize verifier data: {e}"))?;
+    Ok((header, VerifierState { state }))
+}
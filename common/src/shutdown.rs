@@ -0,0 +1,77 @@
+//! Graceful shutdown on `SIGINT` and `SIGTERM`.
+//!
+//! The first signal triggers the [`Shutdown`], such that the binaries stop
+//! taking new work and wrap up; a second signal exits the process immediately.
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// The exit code used when a second signal forces the process to exit.
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// A handle on the shutdown of the process, cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    /// Listens for `SIGINT` and `SIGTERM` in the background.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn on_signal() -> Self {
+        let (tx, rx) = watch::channel(false);
+        tokio::spawn(async move {
+            let name = signal().await;
+            info!("received {name}, shutting down gracefully; send it again to exit immediately");
+            let _ = tx.send(true);
+
+            let name = signal().await;
+            warn!("received {name} again, exiting immediately");
+            std::process::exit(FORCED_EXIT_CODE);
+        });
+        Self(rx)
+    }
+
+    /// Returns whether the shutdown was triggered.
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once the shutdown is triggered.
+    pub async fn wait(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|triggered| *triggered).await.is_err() {
+            // The signal listener is gone, the shutdown will never be triggered.
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Resolves with the name of the first `SIGINT` or `SIGTERM` received.
+#[cfg(unix)]
+async fn signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            error!("failed to listen for SIGTERM: {e}");
+            return interrupt().await;
+        }
+    };
+    tokio::select! {
+        name = interrupt() => name,
+        _ = terminate.recv() => "SIGTERM",
+    }
+}
+
+#[cfg(not(unix))]
+async fn signal() -> &'static str {
+    interrupt().await
+}
+
+async fn interrupt() -> &'static str {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("failed to listen for SIGINT: {e}");
+        std::future::pending::<()>().await;
+    }
+    "SIGINT"
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use alloy::transports::http::reqwest::Url;
 use clap::{Parser, Subcommand, ValueHint};
-use common::prover_state::cli::CliProverStateConfig;
+use common::{format::Format, prover_state::cli::CliProverStateConfig};
 
 /// zero-bin leader config
 #[derive(Parser)]
@@ -17,6 +17,18 @@ pub(crate) struct Cli {
     // mode.
     #[clap(flatten)]
     pub(crate) prover_state_config: CliProverStateConfig,
+
+    /// The format in which proofs are written. Proofs are read in any
+    /// format.
+    #[clap(flatten)]
+    pub(crate) format: Format,
+
+    /// When proving several blocks, stop at the first block with transactions
+    /// requiring a larger circuit configuration rather than failing right
+    /// away: the proofs of the preceding blocks are output, and the progress
+    /// saved, before the run fails naming the block.
+    #[arg(long, default_value_t = false)]
+    pub(crate) skip_blocks_exceeding_circuits: bool,
 }
 
 #[derive(Subcommand, Clone)]
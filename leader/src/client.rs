@@ -2,14 +2,21 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use alloy::transports::http::reqwest::Url;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use common::block_interval::BlockInterval;
+use common::format::Format;
 use common::fs::generate_block_proof_file_name;
+use common::proof::ProofEnvelope;
+use common::prover_state::circuit::CircuitConfig;
+use common::shutdown::Shutdown;
 use paladin::runtime::Runtime;
 use proof_gen::proof_types::GeneratedBlockProof;
+use prover::ProvedBlocks;
 use rpc::{retry::build_http_retry_provider, RpcType};
 use tracing::{error, info, warn};
 
+use crate::progress::Progress;
+
 #[derive(Debug)]
 pub struct RpcParams {
     pub rpc_url: Url,
@@ -24,27 +31,46 @@ pub struct ProofParams {
     pub previous_proof: Option<GeneratedBlockProof>,
     pub proof_output_dir: Option<PathBuf>,
     pub save_inputs_on_error: bool,
+    pub skip_blocks_exceeding_circuits: bool,
     pub keep_intermediate_proofs: bool,
+    pub proof_format: Format,
+    /// The circuits the proofs are generated with, if known to the leader.
+    pub circuit_config: Option<CircuitConfig>,
 }
 
 /// The main function for the client.
+///
+/// Fetching the blocks is cancelled once `shutdown` is triggered. Once it is
+/// triggered, or proving stopped at a block requiring a larger circuit
+/// configuration, the proofs of the blocks proven so far are kept, and the
+/// progress is saved such that the run can be resumed. The run then fails
+/// once the proofs are output, as blocks were left unproven.
 pub(crate) async fn client_main(
     runtime: Runtime,
     rpc_params: RpcParams,
     block_interval: BlockInterval,
     mut params: ProofParams,
+    shutdown: Shutdown,
 ) -> Result<()> {
+    let provider = build_http_retry_provider(
+        rpc_params.rpc_url,
+        rpc_params.backoff,
+        rpc_params.max_retries,
+    );
     let prover_input = rpc::prover_input(
-        &build_http_retry_provider(
-            rpc_params.rpc_url,
-            rpc_params.backoff,
-            rpc_params.max_retries,
-        ),
-        block_interval,
+        &provider,
+        block_interval.clone(),
         params.checkpoint_block_number.into(),
         rpc_params.rpc_type,
-    )
-    .await?;
+    );
+    let prover_input = tokio::select! {
+        prover_input = prover_input => prover_input?,
+        _ = shutdown.wait() => {
+            runtime.close().await?;
+            Progress::unfetched(&block_interval).save(params.proof_output_dir.as_deref())?;
+            bail!("interrupted while fetching the blocks of {block_interval}, no block was proven");
+        }
+    };
 
     if cfg!(feature = "test_only") {
         info!("All proof witnesses have been generated successfully.");
@@ -52,6 +78,12 @@ pub(crate) async fn client_main(
         info!("All proofs have been generated successfully.");
     }
 
+    let blocks: Vec<_> = prover_input
+        .blocks
+        .iter()
+        .map(|block| block.get_block_number().to::<u64>())
+        .collect();
+
     // If `keep_intermediate_proofs` is not set we only keep the last block
     // proof from the interval. It contains all the necessary information to
     // verify the whole sequence.
@@ -60,11 +92,31 @@ pub(crate) async fn client_main(
             &runtime,
             params.previous_proof.take(),
             params.save_inputs_on_error,
+            params.skip_blocks_exceeding_circuits,
             params.proof_output_dir.clone(),
+            params.proof_format,
+            params.circuit_config.as_ref(),
+            &shutdown,
         )
         .await;
     runtime.close().await?;
-    let proved_blocks = proved_blocks?;
+    let ProvedBlocks {
+        proved: proved_blocks,
+        skipped,
+    } = proved_blocks?;
+
+    // Blocks are only left unproven once `shutdown` is triggered, or proving
+    // stopped at a skipped block.
+    let unproven = blocks.len() - proved_blocks.len();
+    if unproven > 0 {
+        Progress::new(
+            blocks,
+            proved_blocks.iter().map(|(block, _)| *block).collect(),
+            skipped,
+            params.proof_output_dir.as_deref(),
+        )
+        .save(params.proof_output_dir.as_deref())?;
+    }
 
     if params.keep_intermediate_proofs {
         if params.proof_output_dir.is_some() {
@@ -72,12 +124,15 @@ pub(crate) async fn client_main(
             warn!("Skipping cleanup, intermediate proof files are kept");
         } else {
             // Output all proofs to stdout
-            std::io::stdout().write_all(&serde_json::to_vec(
-                &proved_blocks
-                    .into_iter()
-                    .filter_map(|(_, block)| block)
-                    .collect::<Vec<_>>(),
-            )?)?;
+            std::io::stdout().write_all(
+                &params.proof_format.to_vec(
+                    &proved_blocks
+                        .into_iter()
+                        .filter_map(|(_, block)| block)
+                        .map(|block| ProofEnvelope::new(block, params.circuit_config.as_ref()))
+                        .collect::<Vec<_>>(),
+                )?,
+            )?;
         }
     } else if let Some(proof_output_dir) = params.proof_output_dir.as_ref() {
         // Remove intermediary proof files
@@ -100,10 +155,20 @@ pub(crate) async fn client_main(
             .filter_map(|(_, block)| block)
             .last()
         {
-            std::io::stdout().write_all(&serde_json::to_vec(&last_block)?)?;
+            std::io::stdout().write_all(&params.proof_format.to_vec(&ProofEnvelope::new(
+                last_block,
+                params.circuit_config.as_ref(),
+            ))?)?;
         }
     }
 
+    if let Some(block) = skipped {
+        bail!("block {block} requires a larger circuit configuration, it and the following blocks were not proven");
+    }
+    if unproven > 0 {
+        bail!("interrupted, {unproven} blocks were left unproven");
+    }
+
     Ok(())
 }
 
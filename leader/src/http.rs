@@ -3,19 +3,29 @@ use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use alloy::primitives::U256;
 use anyhow::{bail, Result};
 use axum::{http::StatusCode, routing::post, Json, Router};
+use common::{
+    format::Format,
+    proof::{BlockProofFile, ProofEnvelope},
+    prover_state::circuit::CircuitConfig,
+    shutdown::Shutdown,
+};
 use paladin::runtime::Runtime;
-use proof_gen::proof_types::GeneratedBlockProof;
 use prover::BlockProverInput;
 use serde::{Deserialize, Serialize};
-use serde_json::to_writer;
 use tracing::{debug, error, info};
 
 /// The main function for the HTTP mode.
+///
+/// Once `shutdown` is triggered, no more requests are accepted, and the
+/// requests being proven are completed before the runtime is closed.
 pub(crate) async fn http_main(
     runtime: Runtime,
     port: u16,
     output_dir: PathBuf,
     save_inputs_on_error: bool,
+    format: Format,
+    circuit_config: Option<CircuitConfig>,
+    shutdown: Shutdown,
 ) -> Result<()> {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     debug!("listening on {}", addr);
@@ -25,11 +35,28 @@ pub(crate) async fn http_main(
         "/prove",
         post({
             let runtime = runtime.clone();
-            move |body| prove(body, runtime, output_dir.clone(), save_inputs_on_error)
+            move |body| {
+                prove(
+                    body,
+                    runtime,
+                    output_dir.clone(),
+                    save_inputs_on_error,
+                    format,
+                    circuit_config.clone(),
+                )
+            }
         }),
     );
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    Ok(axum::serve(listener, app).await?)
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .await?;
+
+    // The router, and the runtime handles it held, are dropped once served.
+    if let Some(runtime) = Arc::into_inner(runtime) {
+        runtime.close().await?;
+    }
+    Ok(())
 }
 
 /// Writes the generated block proof to a file.
@@ -38,15 +65,16 @@ pub(crate) async fn http_main(
 fn write_to_file(
     output_dir: PathBuf,
     block_number: U256,
-    generated_block_proof: &GeneratedBlockProof,
+    proof_envelope: &ProofEnvelope,
+    format: Format,
 ) -> Result<PathBuf> {
-    let file_name = format!("proof-{}.json", block_number);
+    let file_name = format!("proof-{}.{}", block_number, format.file_extension());
     let fully_qualified_file_name = output_dir.join(file_name);
     let file = std::fs::File::create(fully_qualified_file_name.clone());
 
     match file {
         Ok(file) => {
-            to_writer(file, &generated_block_proof)?;
+            format.to_writer(file, proof_envelope)?;
             Ok(fully_qualified_file_name)
         }
         Err(e) => {
@@ -58,7 +86,7 @@ fn write_to_file(
 #[derive(Serialize, Deserialize, Debug)]
 struct HttpProverInput {
     prover_input: BlockProverInput,
-    previous: Option<GeneratedBlockProof>,
+    previous: Option<BlockProofFile>,
 }
 
 async fn prove(
@@ -66,6 +94,8 @@ async fn prove(
     runtime: Arc<Runtime>,
     output_dir: PathBuf,
     save_inputs_on_error: bool,
+    format: Format,
+    circuit_config: Option<CircuitConfig>,
 ) -> StatusCode {
     debug!("Received payload: {:#?}", payload);
 
@@ -75,12 +105,19 @@ async fn prove(
         .prover_input
         .prove(
             &runtime,
-            payload.previous.map(futures::future::ok),
+            payload
+                .previous
+                .map(|previous| futures::future::ok(previous.into_proof())),
             save_inputs_on_error,
         )
         .await
     {
-        Ok(b_proof) => match write_to_file(output_dir, block_number, &b_proof) {
+        Ok(b_proof) => match write_to_file(
+            output_dir,
+            block_number,
+            &ProofEnvelope::new(b_proof, circuit_config.as_ref()),
+            format,
+        ) {
             Ok(file) => {
                 info!("Successfully wrote proof to {}", file.display());
                 StatusCode::OK
@@ -1,11 +1,14 @@
 use std::env;
-use std::{fs::File, path::PathBuf};
+use std::{fs::File, io::BufReader, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use clap::Parser;
 use cli::Command;
 use client::RpcParams;
 use common::block_interval::BlockInterval;
+use common::proof::BlockProofFile;
+use common::prover_state::circuit::CircuitConfig;
+use common::shutdown::Shutdown;
 use dotenvy::dotenv;
 use ops::register;
 use paladin::runtime::Runtime;
@@ -19,19 +22,27 @@ mod cli;
 mod client;
 mod http;
 mod init;
+mod progress;
 mod stdio;
 mod utils;
 
-fn get_previous_proof(path: Option<PathBuf>) -> Result<Option<GeneratedBlockProof>> {
+fn get_previous_proof(
+    path: Option<PathBuf>,
+    circuit_config: Option<&CircuitConfig>,
+) -> Result<Option<GeneratedBlockProof>> {
     if path.is_none() {
         return Ok(None);
     }
 
     let path = path.unwrap();
     let file = File::open(path)?;
-    let des = &mut serde_json::Deserializer::from_reader(&file);
-    let proof: GeneratedBlockProof = serde_path_to_error::deserialize(des)?;
-    Ok(Some(proof))
+    let proof: BlockProofFile = common::format::from_reader(BufReader::new(file))?;
+    if let (Some(metadata), Some(circuit_config)) = (proof.metadata(), circuit_config) {
+        metadata
+            .check_circuits(circuit_config)
+            .context("previous proof is incompatible with the configured circuits")?;
+    }
+    Ok(Some(proof.into_proof()))
 }
 
 #[tokio::main]
@@ -59,23 +70,37 @@ async fn main() -> Result<()> {
     }
 
     let args = cli::Cli::parse();
+    let prover_state_manager = args.prover_state_config.into_prover_state_manager()?;
     if let paladin::config::Runtime::InMemory = args.paladin.runtime {
         // If running in emulation mode, we'll need to initialize the prover
         // state here.
-        args.prover_state_config
-            .into_prover_state_manager()
-            .initialize()?;
+        prover_state_manager.initialize()?;
     }
+    // Remote workers may be configured with other circuits than the leader, so
+    // only with the in-memory runtime are the proofs known to be generated
+    // with these.
+    let circuit_config = matches!(args.paladin.runtime, paladin::config::Runtime::InMemory)
+        .then_some(prover_state_manager.circuit_config);
 
     let runtime = Runtime::from_config(&args.paladin, register()).await?;
+    let shutdown = Shutdown::on_signal();
 
     match args.command.clone() {
         Command::Stdio {
             previous_proof,
             save_inputs_on_error,
         } => {
-            let previous_proof = get_previous_proof(previous_proof)?;
-            stdio::stdio_main(runtime, previous_proof, save_inputs_on_error).await?;
+            let previous_proof = get_previous_proof(previous_proof, circuit_config.as_ref())?;
+            stdio::stdio_main(
+                runtime,
+                previous_proof,
+                save_inputs_on_error,
+                args.skip_blocks_exceeding_circuits,
+                args.format,
+                circuit_config,
+                shutdown,
+            )
+            .await?;
         }
         Command::Http {
             port,
@@ -91,7 +116,16 @@ async fn main() -> Result<()> {
                 panic!("output-dir is not a writable directory");
             }
 
-            http::http_main(runtime, port, output_dir, save_inputs_on_error).await?;
+            http::http_main(
+                runtime,
+                port,
+                output_dir,
+                save_inputs_on_error,
+                args.format,
+                circuit_config,
+                shutdown,
+            )
+            .await?;
         }
         Command::Jerigon {
             rpc_url,
@@ -117,7 +151,7 @@ async fn main() -> Result<()> {
             backoff,
             max_retries,
         } => {
-            let previous_proof = get_previous_proof(previous_proof)?;
+            let previous_proof = get_previous_proof(previous_proof, circuit_config.as_ref())?;
             let mut block_interval = BlockInterval::new(&block_interval)?;
 
             if let BlockInterval::FollowFrom {
@@ -143,8 +177,12 @@ async fn main() -> Result<()> {
                     previous_proof,
                     proof_output_dir,
                     save_inputs_on_error,
+                    skip_blocks_exceeding_circuits: args.skip_blocks_exceeding_circuits,
                     keep_intermediate_proofs,
+                    proof_format: args.format,
+                    circuit_config,
                 },
+                shutdown,
             )
             .await?;
         }
@@ -0,0 +1,98 @@
+//! The progress of a run interrupted by a shutdown, or stopped at a block
+//! requiring a larger circuit configuration, such that it can be resumed.
+use std::path::{Path, PathBuf};
+
+use alloy::{primitives::BlockNumber, rpc::types::eth::BlockId};
+use anyhow::{Context as _, Result};
+use common::{block_interval::BlockInterval, fs::generate_block_proof_file_name};
+use serde::Serialize;
+use tracing::warn;
+
+/// The name of the progress file written to the proof output directory.
+const PROGRESS_FILE: &str = "progress.json";
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Progress {
+    /// The block interval of the run, if it stopped before its blocks were
+    /// fetched, in which case the unproven blocks are only known for bounded
+    /// intervals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_interval: Option<String>,
+    /// The blocks proven before the run stopped.
+    proven_blocks: Vec<BlockNumber>,
+    /// The blocks left unproven, to prove in the next run.
+    unproven_blocks: Vec<BlockNumber>,
+    /// The block at which the run stopped as it requires a larger circuit
+    /// configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped_block: Option<BlockNumber>,
+    /// The proof of the last proven block, to give as `--previous-proof` in
+    /// the next run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_proof: Option<PathBuf>,
+}
+
+impl Progress {
+    /// Returns the progress of proving `blocks`, given the `proven_blocks`,
+    /// the `skipped_block` if any and the directory their proofs were written
+    /// to.
+    pub(crate) fn new(
+        blocks: Vec<BlockNumber>,
+        proven_blocks: Vec<BlockNumber>,
+        skipped_block: Option<BlockNumber>,
+        proof_output_dir: Option<&Path>,
+    ) -> Self {
+        let unproven_blocks = blocks
+            .into_iter()
+            .filter(|block| !proven_blocks.contains(block))
+            .collect();
+        let previous_proof = proof_output_dir
+            .zip(proven_blocks.last())
+            .map(|(dir, block)| dir.join(generate_block_proof_file_name(&None, *block)));
+        Self {
+            block_interval: None,
+            proven_blocks,
+            unproven_blocks,
+            skipped_block,
+            previous_proof,
+        }
+    }
+
+    /// Returns the progress of a run stopped while fetching the blocks of
+    /// `block_interval`, i.e. before any block was proven.
+    pub(crate) fn unfetched(block_interval: &BlockInterval) -> Self {
+        let unproven_blocks = match block_interval {
+            BlockInterval::SingleBlockId(BlockId::Number(block)) => {
+                block.as_number().into_iter().collect()
+            }
+            BlockInterval::Range(range) => range.clone().collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            block_interval: Some(block_interval.to_string()),
+            proven_blocks: Vec::new(),
+            unproven_blocks,
+            skipped_block: None,
+            previous_proof: None,
+        }
+    }
+
+    /// Writes the progress to `progress.json` in `dir`, or logs it if no
+    /// directory is given.
+    pub(crate) fn save(&self, dir: Option<&Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        match dir {
+            Some(dir) => {
+                // The directory is only created once a proof is written.
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create {}", dir.display()))?;
+                let path = dir.join(PROGRESS_FILE);
+                std::fs::write(&path, json)
+                    .with_context(|| format!("failed to write progress to {}", path.display()))?;
+                warn!("Stopped, progress written to {}", path.display());
+            }
+            None => warn!("Stopped, progress: {json}"),
+        }
+        Ok(())
+    }
+}
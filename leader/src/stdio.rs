@@ -1,42 +1,92 @@
 use std::io::{Read, Write};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use common::format::Format;
+use common::proof::ProofEnvelope;
+use common::prover_state::circuit::CircuitConfig;
+use common::shutdown::Shutdown;
 use paladin::runtime::Runtime;
 use proof_gen::proof_types::GeneratedBlockProof;
-use prover::ProverInput;
+use prover::{ProvedBlocks, ProverInput};
 use tracing::info;
 
+use crate::progress::Progress;
+
 /// The main function for the stdio mode.
+///
+/// Once `shutdown` is triggered, or proving stopped at a block requiring a
+/// larger circuit configuration, the proofs of the blocks proven so far are
+/// written to stdout, and the progress is logged. The run then fails once the
+/// proofs are written, as blocks were left unproven.
 pub(crate) async fn stdio_main(
     runtime: Runtime,
     previous: Option<GeneratedBlockProof>,
     save_inputs_on_error: bool,
+    skip_blocks_exceeding_circuits: bool,
+    format: Format,
+    circuit_config: Option<CircuitConfig>,
+    shutdown: Shutdown,
 ) -> Result<()> {
-    let mut buffer = String::new();
-    std::io::stdin().read_to_string(&mut buffer)?;
+    let mut buffer = Vec::new();
+    std::io::stdin().read_to_end(&mut buffer)?;
 
-    let des = &mut serde_json::Deserializer::from_str(&buffer);
     let prover_input = ProverInput {
-        blocks: serde_path_to_error::deserialize(des)?,
+        blocks: common::format::from_slice(&buffer)?,
     };
+    let blocks: Vec<_> = prover_input
+        .blocks
+        .iter()
+        .map(|block| block.get_block_number().to::<u64>())
+        .collect();
 
     let proved_blocks = prover_input
-        .prove(&runtime, previous, save_inputs_on_error, None)
+        .prove(
+            &runtime,
+            previous,
+            save_inputs_on_error,
+            skip_blocks_exceeding_circuits,
+            None,
+            format,
+            circuit_config.as_ref(),
+            &shutdown,
+        )
         .await;
     runtime.close().await?;
-    let proved_blocks = proved_blocks?;
+    let ProvedBlocks {
+        proved: proved_blocks,
+        skipped,
+    } = proved_blocks?;
 
-    if cfg!(feature = "test_only") {
+    // Blocks are only left unproven once `shutdown` is triggered, or proving
+    // stopped at a skipped block.
+    let unproven = blocks.len() - proved_blocks.len();
+    if unproven > 0 {
+        Progress::new(
+            blocks,
+            proved_blocks.iter().map(|(block, _)| *block).collect(),
+            skipped,
+            None,
+        )
+        .save(None)?;
+    } else if cfg!(feature = "test_only") {
         info!("All proof witnesses have been generated successfully.");
     } else {
         info!("All proofs have been generated successfully.");
     }
 
-    let proofs: Vec<GeneratedBlockProof> = proved_blocks
+    let proofs: Vec<ProofEnvelope> = proved_blocks
         .into_iter()
         .filter_map(|(_, proof)| proof)
+        .map(|proof| ProofEnvelope::new(proof, circuit_config.as_ref()))
         .collect();
-    std::io::stdout().write_all(&serde_json::to_vec(&proofs)?)?;
+    std::io::stdout().write_all(&format.to_vec(&proofs)?)?;
+
+    if let Some(block) = skipped {
+        bail!("block {block} requires a larger circuit configuration, it and the following blocks were not proven");
+    }
+    if unproven > 0 {
+        bail!("interrupted, {unproven} blocks were left unproven");
+    }
 
     Ok(())
 }
@@ -1,6 +1,9 @@
 use std::time::Instant;
 
-use common::{debug_utils::save_inputs_to_disk, prover_state::p_state};
+use common::{
+    debug_utils::save_inputs_to_disk,
+    prover_state::{circuit::CircuitRangeError, p_state},
+};
 use evm_arithmetization::{proof::PublicValues, GenerationInputs};
 use keccak_hash::keccak;
 use paladin::{
@@ -14,8 +17,17 @@ use proof_gen::{
 use serde::{Deserialize, Serialize};
 use tracing::{error, event, info_span, Level};
 
+pub mod tasks;
+
 registry!();
 
+/// The outcome of a transaction proof, or of the aggregation of several.
+///
+/// A transaction requiring a larger circuit configuration is not a fatal
+/// error of the operation, but reported as such to the leader, which may skip
+/// its block.
+pub type TxProofOutcome = std::result::Result<AggregatableProof, CircuitRangeError>;
+
 #[derive(Deserialize, Serialize, RemoteExecute)]
 pub struct TxProof {
     pub save_inputs_on_error: bool,
@@ -24,33 +36,33 @@ pub struct TxProof {
 #[cfg(not(feature = "test_only"))]
 impl Operation for TxProof {
     type Input = GenerationInputs;
-    type Output = proof_gen::proof_types::AggregatableProof;
+    type Output = TxProofOutcome;
 
     fn execute(&self, input: Self::Input) -> Result<Self::Output> {
         let _span = TxProofSpan::new(&input);
-        let proof = if self.save_inputs_on_error {
-            common::prover_state::p_manager()
-                .generate_txn_proof(input.clone())
-                .map_err(|err| {
-                    if let Err(write_err) = save_inputs_to_disk(
-                        format!(
-                            "b{}_txn_{}_input.log",
-                            input.block_metadata.block_number, input.txn_number_before
-                        ),
-                        input,
-                    ) {
-                        error!("Failed to save txn proof input to disk: {:?}", write_err);
-                    }
+        let saved_input = self.save_inputs_on_error.then(|| input.clone());
+        let err = match common::prover_state::p_manager().generate_txn_proof(input) {
+            Ok(proof) => return Ok(Ok(proof.into())),
+            Err(err) => err,
+        };
 
-                    FatalError::from_anyhow(err, FatalStrategy::Terminate)
-                })?
-        } else {
-            common::prover_state::p_manager()
-                .generate_txn_proof(input)
-                .map_err(|err| FatalError::from_anyhow(err, FatalStrategy::Terminate))?
+        let err = match err.downcast::<CircuitRangeError>() {
+            Ok(range_err) => return Ok(Err(range_err)),
+            Err(err) => err,
         };
+        if let Some(input) = saved_input {
+            if let Err(write_err) = save_inputs_to_disk(
+                format!(
+                    "b{}_txn_{}_input.log",
+                    input.block_metadata.block_number, input.txn_number_before
+                ),
+                input,
+            ) {
+                error!("Failed to save txn proof input to disk: {:?}", write_err);
+            }
+        }
 
-        Ok(proof.into())
+        Err(FatalError::from_anyhow(err, FatalStrategy::Terminate))
     }
 }
 
@@ -92,10 +104,12 @@ impl Operation for TxProof {
 
 /// RAII struct to measure the time taken by a transaction proof.
 ///
-/// - When created, it starts a span with the transaction proof id.
+/// - When created, it starts a span with the transaction proof id and registers
+///   the proof as a running task.
 /// - When dropped, it logs the time taken by the transaction proof.
 struct TxProofSpan {
     _span: tracing::span::EnteredSpan,
+    _task: tasks::TaskGuard,
     start: Instant,
     descriptor: String,
 }
@@ -126,10 +140,12 @@ impl TxProofSpan {
     fn new(ir: &GenerationInputs) -> Self {
         let id = Self::get_id(ir);
         let span = info_span!("p_gen", id).entered();
+        let task = tasks::start("TxProof", id);
         let start = Instant::now();
         let descriptor = Self::get_descriptor(ir);
         Self {
             _span: span,
+            _task: task,
             start,
             descriptor,
         }
@@ -160,9 +176,18 @@ fn get_agg_proof_public_values(elem: AggregatableProof) -> PublicValues {
 }
 
 impl Monoid for AggProof {
-    type Elem = AggregatableProof;
+    type Elem = TxProofOutcome;
 
     fn combine(&self, a: Self::Elem, b: Self::Elem) -> Result<Self::Elem> {
+        let (a, b) = match (a, b) {
+            (Ok(a), Ok(b)) => (a, b),
+            (Err(err), _) | (_, Err(err)) => return Ok(Err(err)),
+        };
+        let block_number = match &a {
+            AggregatableProof::Txn(info) => info.p_vals.block_metadata.block_number,
+            AggregatableProof::Agg(info) => info.p_vals.block_metadata.block_number,
+        };
+        let _task = tasks::start("AggProof", format!("b{block_number}"));
         let result = generate_agg_proof(p_state(), &a, &b).map_err(|e| {
             if self.save_inputs_on_error {
                 let pv = vec![
@@ -183,7 +208,7 @@ impl Monoid for AggProof {
             FatalError::from(e)
         })?;
 
-        Ok(result.into())
+        Ok(Ok(result.into()))
     }
 
     fn empty(&self) -> Self::Elem {
@@ -203,6 +228,10 @@ impl Operation for BlockProof {
     type Output = GeneratedBlockProof;
 
     fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let _task = tasks::start(
+            "BlockProof",
+            format!("b{}", input.p_vals.block_metadata.block_number),
+        );
         Ok(
             generate_block_proof(p_state(), self.prev.as_ref(), &input).map_err(|e| {
                 if self.save_inputs_on_error {
@@ -0,0 +1,164 @@
+//! Tracking of the operations being executed by this process, such that the
+//! worker can report them, and holding of the operations received once it
+//! drains.
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use serde::Serialize;
+use tracing::warn;
+
+/// The operations of this process.
+static TASKS: Tasks = Tasks::new();
+
+/// Tracks the operations being executed, and whether new ones are held.
+struct Tasks {
+    /// The operations being executed, by registration order.
+    running: Mutex<BTreeMap<u64, (&'static str, String, Instant)>>,
+    /// The key of the next operation registered.
+    next_key: AtomicU64,
+    /// Whether the operations started from now on are held, see [`hold_new`].
+    hold_new: AtomicBool,
+}
+
+/// An operation being executed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningTask {
+    /// The name of the operation, e.g. `TxProof`.
+    pub operation: &'static str,
+    /// Identifies the input of the operation, e.g. `b19240705 - 3` for a
+    /// transaction proof.
+    pub id: String,
+    /// The time elapsed since the operation started, in seconds.
+    pub elapsed_secs: u64,
+}
+
+impl Tasks {
+    const fn new() -> Self {
+        Self {
+            running: Mutex::new(BTreeMap::new()),
+            next_key: AtomicU64::new(0),
+            hold_new: AtomicBool::new(false),
+        }
+    }
+
+    fn running(&self) -> Vec<RunningTask> {
+        self.running
+            .lock()
+            .expect("tasks lock poisoned")
+            .values()
+            .map(|(operation, id, start)| RunningTask {
+                operation,
+                id: id.clone(),
+                elapsed_secs: start.elapsed().as_secs(),
+            })
+            .collect()
+    }
+
+    fn hold_new(&self) {
+        self.hold_new.store(true, Ordering::Release);
+    }
+
+    fn start(&'static self, operation: &'static str, id: String) -> TaskGuard {
+        if self.hold_new.load(Ordering::Acquire) {
+            warn!("Holding {operation} ({id}) until the worker exits, to be requeued");
+            loop {
+                std::thread::park();
+            }
+        }
+
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        self.running
+            .lock()
+            .expect("tasks lock poisoned")
+            .insert(key, (operation, id, Instant::now()));
+        TaskGuard(self, key)
+    }
+}
+
+/// Returns the operations being executed, oldest first.
+pub fn running() -> Vec<RunningTask> {
+    TASKS.running()
+}
+
+/// Holds the operations started from now on rather than executing them, until
+/// the process exits.
+///
+/// Their tasks are never completed, such that the broker requeues them for
+/// other workers once this worker disconnects. This relies on the paladin
+/// worker acknowledging a task only once its operation has executed and its
+/// result is published: the task of a held operation is unacknowledged, and
+/// AMQP brokers requeue the unacknowledged deliveries of a connection once it
+/// closes, whatever the exit status of the process.
+///
+/// Only worker processes may call this. Operations executed in process by
+/// the in-memory runtime have no broker to be requeued by, so holding them
+/// would hang the leader.
+pub fn hold_new() {
+    TASKS.hold_new();
+}
+
+/// Registers an operation as running until the returned guard is dropped.
+///
+/// Never returns once [`hold_new`] was called.
+pub(crate) fn start(operation: &'static str, id: String) -> TaskGuard {
+    TASKS.start(operation, id)
+}
+
+/// Unregisters the operation when dropped.
+pub(crate) struct TaskGuard(&'static Tasks, u64);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.0.running.lock() {
+            running.remove(&self.1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::mpsc, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn tracks_running_tasks() {
+        let first = start("TxProof", "b1 - 0".to_string());
+        let second = start("AggProof", "b1".to_string());
+        let ids = |tasks: Vec<RunningTask>| -> Vec<String> {
+            tasks.into_iter().map(|task| task.id).collect()
+        };
+        assert_eq!(ids(running()), ["b1 - 0", "b1"]);
+
+        drop(first);
+        assert_eq!(ids(running()), ["b1"]);
+        drop(second);
+        assert!(running().is_empty());
+    }
+
+    #[test]
+    fn holds_new_tasks() {
+        static HELD: Tasks = Tasks::new();
+        let running = HELD.start("TxProof", "b1 - 0".to_string());
+        HELD.hold_new();
+
+        let (started, on_start) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _task = HELD.start("TxProof", "b1 - 1".to_string());
+            started.send(()).ok();
+        });
+        assert!(on_start.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // The operation running before is not affected, and the held one is
+        // not reported.
+        assert_eq!(HELD.running().len(), 1);
+        drop(running);
+        assert!(HELD.running().is_empty());
+    }
+}
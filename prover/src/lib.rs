@@ -1,9 +1,16 @@
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use alloy::primitives::{BlockNumber, U256};
 use anyhow::{Context, Result};
-use common::fs::generate_block_proof_file_name;
+use common::{
+    format::Format,
+    fs::generate_block_proof_file_name,
+    proof::ProofEnvelope,
+    prover_state::circuit::{CircuitConfig, CircuitRangeError},
+    shutdown::Shutdown,
+};
+use evm_arithmetization::GenerationInputs;
 use futures::{future::BoxFuture, stream::FuturesOrdered, FutureExt, TryFutureExt, TryStreamExt};
 use num_traits::ToPrimitive as _;
 use ops::TxProof;
@@ -20,7 +27,7 @@ use trace_decoder::{
     trace_protocol::BlockTrace,
     types::{CodeHash, OtherBlockData},
 };
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BlockProverInput {
@@ -36,6 +43,13 @@ impl BlockProverInput {
         self.other_data.b_data.b_meta.block_number.into()
     }
 
+    /// Decodes the block trace into the inputs of each transaction proof.
+    pub fn into_txn_inputs(self) -> Result<Vec<GenerationInputs>> {
+        Ok(self
+            .block_trace
+            .into_txn_proof_gen_ir(&ProcessingMeta::new(resolve_code_hash_fn), self.other_data)?)
+    }
+
     #[cfg(not(feature = "test_only"))]
     pub async fn prove(
         self,
@@ -47,11 +61,7 @@ impl BlockProverInput {
 
         let block_number = self.get_block_number();
 
-        let other_data = self.other_data;
-        let txs = self.block_trace.into_txn_proof_gen_ir(
-            &ProcessingMeta::new(resolve_code_hash_fn),
-            other_data.clone(),
-        )?;
+        let txs = self.into_txn_inputs()?;
 
         let agg_proof = IndexedStream::from(txs)
             .map(&TxProof {
@@ -61,7 +71,7 @@ impl BlockProverInput {
                 save_inputs_on_error,
             })
             .run(runtime)
-            .await?;
+            .await??;
 
         if let proof_gen::proof_types::AggregatableProof::Agg(proof) = agg_proof {
             let block_number = block_number
@@ -97,11 +107,7 @@ impl BlockProverInput {
         let block_number = self.get_block_number();
         info!("Testing witness generation for block {block_number}.");
 
-        let other_data = self.other_data;
-        let txs = self.block_trace.into_txn_proof_gen_ir(
-            &ProcessingMeta::new(resolve_code_hash_fn),
-            other_data.clone(),
-        )?;
+        let txs = self.into_txn_inputs()?;
 
         IndexedStream::from(txs)
             .map(&TxProof {
@@ -127,21 +133,53 @@ pub struct ProverInput {
     pub blocks: Vec<BlockProverInput>,
 }
 
+/// The blocks proven by [`ProverInput::prove`].
+#[derive(Debug, Default)]
+pub struct ProvedBlocks {
+    /// The proven blocks, along with their proof if not written to disk.
+    pub proved: Vec<(BlockNumber, Option<GeneratedBlockProof>)>,
+    /// The block skipped as it requires a larger circuit configuration, at
+    /// which proving stopped.
+    pub skipped: Option<BlockNumber>,
+}
+
+/// The outcome of proving a single block of a [`ProverInput`].
+enum BlockOutcome {
+    Proved(BlockNumber, Option<GeneratedBlockProof>),
+    Skipped(BlockNumber),
+}
+
 impl ProverInput {
     /// Prove all the blocks in the input.
     /// Return the list of block numbers that are proved and if the proof data
     /// is not saved to disk, return the generated block proofs as well.
+    /// Proofs saved to disk are written in the given `proof_format`, wrapped
+    /// in a [`ProofEnvelope`] describing the `circuit_config`, if known.
+    ///
+    /// If `skip_blocks_exceeding_circuits` is set, a block requiring a larger
+    /// circuit configuration is skipped rather than failing. As the following
+    /// blocks can't be linked to the previous proof without it, no more blocks
+    /// are proven, and the skipped block is returned along with the blocks
+    /// proven before it.
+    ///
+    /// Once `shutdown` is triggered, no more blocks are proven and the blocks
+    /// proven so far are returned.
+    #[allow(clippy::too_many_arguments)]
     pub async fn prove(
         self,
         runtime: &Runtime,
         previous_proof: Option<GeneratedBlockProof>,
         save_inputs_on_error: bool,
+        skip_blocks_exceeding_circuits: bool,
         proof_output_dir: Option<PathBuf>,
-    ) -> Result<Vec<(BlockNumber, Option<GeneratedBlockProof>)>> {
+        proof_format: Format,
+        circuit_config: Option<&CircuitConfig>,
+        shutdown: &Shutdown,
+    ) -> Result<ProvedBlocks> {
         let mut prev: Option<BoxFuture<Result<GeneratedBlockProof>>> =
             previous_proof.map(|proof| Box::pin(futures::future::ok(proof)) as BoxFuture<_>);
 
-        let results: FuturesOrdered<_> = self
+        let mut results: FuturesOrdered<_> = self
             .blocks
             .into_iter()
             .map(|block| {
@@ -152,16 +190,35 @@ impl ProverInput {
 
                 // Prove the block
                 let proof_output_dir = proof_output_dir.clone();
+                let circuit_config = circuit_config.cloned();
                 let fut = block
                     .prove(runtime, prev.take(), save_inputs_on_error)
                     .then(move |proof| async move {
-                        let proof = proof?;
+                        let proof = match proof {
+                            Ok(proof) => proof,
+                            Err(e)
+                                if skip_blocks_exceeding_circuits
+                                    && e.is::<CircuitRangeError>() =>
+                            {
+                                warn!("Skipping block {block_number}: {e:#}");
+                                // Dropping `tx` fails the next block, which is never awaited as
+                                // proving stops at this block.
+                                return Ok(BlockOutcome::Skipped(block_number.to()));
+                            }
+                            Err(e) => return Err(e),
+                        };
                         let block_number = proof.b_height;
 
                         // Write latest generated proof to disk if proof_output_dir is provided
                         let return_proof: Option<GeneratedBlockProof> =
                             if proof_output_dir.is_some() {
-                                ProverInput::write_proof(proof_output_dir, &proof).await?;
+                                ProverInput::write_proof(
+                                    proof_output_dir,
+                                    &proof,
+                                    circuit_config.as_ref(),
+                                    proof_format,
+                                )
+                                .await?;
                                 None
                             } else {
                                 Some(proof.clone())
@@ -171,7 +228,7 @@ impl ProverInput {
                             anyhow::bail!("Failed to send proof");
                         }
 
-                        Ok((block_number, return_proof))
+                        Ok(BlockOutcome::Proved(block_number, return_proof))
                     })
                     .boxed();
 
@@ -181,15 +238,46 @@ impl ProverInput {
             })
             .collect();
 
-        results.try_collect().await
+        let mut proved_blocks = ProvedBlocks::default();
+        loop {
+            tokio::select! {
+                result = results.try_next() => match result? {
+                    Some(BlockOutcome::Proved(block_number, proof)) => {
+                        proved_blocks.proved.push((block_number, proof))
+                    }
+                    Some(BlockOutcome::Skipped(block_number)) => {
+                        // Dropping the remaining block futures stops dispatching their tasks.
+                        warn!(
+                            "Stopped proving at block {block_number}, {} following blocks left unproven",
+                            results.len()
+                        );
+                        proved_blocks.skipped = Some(block_number);
+                        break;
+                    }
+                    None => break,
+                },
+                _ = shutdown.wait() => {
+                    // Dropping the remaining block futures stops dispatching their tasks.
+                    warn!(
+                        "Stopped proving after {} blocks, {} blocks left unproven",
+                        proved_blocks.proved.len(),
+                        results.len()
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(proved_blocks)
     }
 
     /// Write the proof to the disk (if `output_dir` is provided) or stdout.
     pub(crate) async fn write_proof(
         output_dir: Option<PathBuf>,
         proof: &GeneratedBlockProof,
+        circuit_config: Option<&CircuitConfig>,
+        format: Format,
     ) -> Result<()> {
-        let proof_serialized = serde_json::to_vec(proof)?;
+        let proof_serialized = format.to_vec(&ProofEnvelope::new(proof.clone(), circuit_config))?;
         let block_proof_file_path =
             output_dir.map(|path| generate_block_proof_file_name(&path.to_str(), proof.b_height));
         match block_proof_file_path {
@@ -198,10 +286,24 @@ impl ProverInput {
                     tokio::fs::create_dir_all(parent).await?;
                 }
 
-                let mut f = tokio::fs::File::create(p).await?;
+                // Write to a temporary file first, such that an interrupted write does not
+                // leave a partial proof file behind. The temporary file is removed if the
+                // write fails, or is dropped on shutdown.
+                let mut tmp_name = p.file_name().unwrap_or_default().to_os_string();
+                tmp_name.push(".tmp");
+                let tmp_path = p.with_file_name(tmp_name);
+                let mut tmp_guard = TmpFileGuard(Some(&tmp_path));
+
+                let mut f = tokio::fs::File::create(&tmp_path).await?;
                 f.write_all(&proof_serialized)
                     .await
-                    .context("Failed to write proof to disk")
+                    .context("Failed to write proof to disk")?;
+                f.sync_all().await?;
+                tokio::fs::rename(&tmp_path, &p)
+                    .await
+                    .context("Failed to write proof to disk")?;
+                tmp_guard.0 = None;
+                Ok(())
             }
             None => tokio::io::stdout()
                 .write_all(&proof_serialized)
@@ -210,3 +312,14 @@ impl ProverInput {
         }
     }
 }
+
+/// Removes the temporary file it holds when dropped.
+struct TmpFileGuard<'a>(Option<&'a Path>);
+
+impl Drop for TmpFileGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(path) = self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
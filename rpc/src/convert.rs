@@ -0,0 +1,74 @@
+use anyhow::anyhow;
+use prover::BlockProverInput;
+use trace_decoder::{
+    compact::compact_prestate_processing::process_compact_prestate,
+    trace_protocol::{
+        BlockTrace, BlockTraceTriePreImages, CombinedPreImages, SeparateStorageTriesPreImage,
+        SeparateTriePreImage, SeparateTriePreImages, TrieDirect,
+    },
+};
+
+/// Converts the trie pre-images of the given prover input into separate state
+/// and storage tries.
+///
+/// See [`separate_trie_pre_images`].
+pub fn block_prover_input(input: BlockProverInput) -> anyhow::Result<BlockProverInput> {
+    Ok(BlockProverInput {
+        block_trace: separate_trie_pre_images(input.block_trace)?,
+        other_data: input.other_data,
+    })
+}
+
+/// Decodes the compact witness of a [`BlockTraceTriePreImages::Combined`]
+/// trace (as produced by Jerigon) into explicit state and storage tries, in
+/// the same [`BlockTraceTriePreImages::Separate`] form produced for native
+/// nodes.
+///
+/// Contract code embedded in the compact witness is merged into the code db
+/// of the returned trace. Traces that already carry separate pre-images are
+/// returned unchanged.
+pub fn separate_trie_pre_images(block_trace: BlockTrace) -> anyhow::Result<BlockTrace> {
+    let BlockTrace {
+        trie_pre_images,
+        code_db,
+        txn_info,
+    } = block_trace;
+
+    let compact = match trie_pre_images {
+        BlockTraceTriePreImages::Combined(CombinedPreImages { compact }) => compact,
+        separate @ BlockTraceTriePreImages::Separate(_) => {
+            return Ok(BlockTrace {
+                trie_pre_images: separate,
+                code_db,
+                txn_info,
+            })
+        }
+    };
+
+    let witness = process_compact_prestate(compact)
+        .map_err(|e| anyhow!("failed to decode compact witness: {e}"))?
+        .witness_out;
+
+    let mut code_db = code_db.unwrap_or_default();
+    code_db.extend(witness.code);
+
+    Ok(BlockTrace {
+        trie_pre_images: BlockTraceTriePreImages::Separate(SeparateTriePreImages {
+            state: SeparateTriePreImage::Direct(TrieDirect(witness.state_trie)),
+            storage: SeparateStorageTriesPreImage::MultipleTries(
+                witness
+                    .storage_tries
+                    .into_iter()
+                    .map(|(hashed_address, trie)| {
+                        (
+                            hashed_address,
+                            SeparateTriePreImage::Direct(TrieDirect(trie)),
+                        )
+                    })
+                    .collect(),
+            ),
+        }),
+        code_db: Some(code_db).filter(|code_db| !code_db.is_empty()),
+        txn_info,
+    })
+}
@@ -0,0 +1,272 @@
+use std::{collections::BTreeSet, fs::File, io::BufReader, path::PathBuf, str::FromStr};
+
+use alloy::{primitives::U256, rpc::types::eth::BlockId};
+use anyhow::{anyhow, Context as _};
+use clap::ValueEnum as _;
+use common::block_interval::BlockInterval;
+use prover::BlockProverInput;
+use serde::Serialize;
+use serde_json::Value;
+use trace_decoder::trace_protocol::TxnInfo;
+use url::Url;
+
+use crate::{retry::build_http_retry_provider, RpcType};
+
+/// Where to obtain the prover input of a block from.
+#[derive(Clone, Debug)]
+pub enum InputSource {
+    /// Fetch the prover input from a node of the given type.
+    Rpc { rpc_type: RpcType, rpc_url: Url },
+    /// Load the prover input from a file previously written by `rpc fetch`.
+    File(PathBuf),
+}
+
+impl FromStr for InputSource {
+    type Err = anyhow::Error;
+
+    /// Parses `jerigon:<RPC_URL>` or `native:<RPC_URL>` as an RPC source, and
+    /// anything else as a path to a prover input file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((prefix, rest)) = s.split_once(':') {
+            if let Ok(rpc_type) = RpcType::from_str(prefix, true) {
+                let rpc_url = rest
+                    .parse()
+                    .map_err(|e| anyhow!("invalid RPC URL '{rest}': {e}"))?;
+                return Ok(InputSource::Rpc { rpc_type, rpc_url });
+            }
+        }
+
+        Ok(InputSource::File(PathBuf::from(s)))
+    }
+}
+
+impl InputSource {
+    /// Obtains the prover input of the given block from this source.
+    pub async fn block_prover_input(
+        &self,
+        block_number: u64,
+        checkpoint_block_id: BlockId,
+        backoff: u64,
+        max_retries: u32,
+    ) -> anyhow::Result<BlockProverInput> {
+        match self {
+            InputSource::Rpc { rpc_type, rpc_url } => {
+                let mut prover_input = crate::prover_input(
+                    &build_http_retry_provider(rpc_url.clone(), backoff, max_retries),
+                    BlockInterval::SingleBlockId(BlockId::Number(block_number.into())),
+                    checkpoint_block_id,
+                    rpc_type.clone(),
+                )
+                .await?;
+                prover_input
+                    .blocks
+                    .pop()
+                    .with_context(|| format!("no prover input returned for block {block_number}"))
+            }
+            InputSource::File(path) => {
+                let blocks: Vec<BlockProverInput> =
+                    common::format::from_reader(BufReader::new(File::open(path)?))
+                        .with_context(|| format!("failed to parse {}", path.display()))?;
+                blocks
+                    .into_iter()
+                    .find(|block| block.get_block_number() == U256::from(block_number))
+                    .with_context(|| {
+                        format!("block {block_number} not found in {}", path.display())
+                    })
+            }
+        }
+    }
+}
+
+/// A single difference between two prover inputs for the same block.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Difference {
+    /// Location of the differing value, e.g.
+    /// `txn_info[3].traces[0x..].balance`.
+    pub path: String,
+    /// The value on the left-hand side, `null` if absent.
+    pub lhs: Value,
+    /// The value on the right-hand side, `null` if absent.
+    pub rhs: Value,
+}
+
+/// Reports the differences between two prover inputs for the same block.
+///
+/// Covers the transaction metadata, the per-account [`TxnTrace`] fields
+/// (balance, nonce, storage reads/writes, code usage and self-destruction)
+/// and the [`OtherBlockData`]. Storage reads are compared regardless of their
+/// order.
+///
+/// [`TxnTrace`]: trace_decoder::trace_protocol::TxnTrace
+/// [`OtherBlockData`]: trace_decoder::types::OtherBlockData
+pub fn block_prover_inputs(
+    lhs: &BlockProverInput,
+    rhs: &BlockProverInput,
+) -> anyhow::Result<Vec<Difference>> {
+    let mut differences = Vec::new();
+
+    let (lhs_txns, rhs_txns) = (&lhs.block_trace.txn_info, &rhs.block_trace.txn_info);
+    if lhs_txns.len() != rhs_txns.len() {
+        differences.push(Difference {
+            path: "txn_info.len()".to_string(),
+            lhs: lhs_txns.len().into(),
+            rhs: rhs_txns.len().into(),
+        });
+    }
+
+    for (index, (lhs_txn, rhs_txn)) in lhs_txns.iter().zip(rhs_txns).enumerate() {
+        txn_infos(
+            &format!("txn_info[{index}]"),
+            lhs_txn,
+            rhs_txn,
+            &mut differences,
+        )?;
+    }
+
+    values(
+        "other_data",
+        &serde_json::to_value(&lhs.other_data)?,
+        &serde_json::to_value(&rhs.other_data)?,
+        &mut differences,
+    );
+
+    Ok(differences)
+}
+
+/// Reports the differences between the metadata and the per-account traces of
+/// two transactions.
+fn txn_infos(
+    path: &str,
+    lhs: &TxnInfo,
+    rhs: &TxnInfo,
+    differences: &mut Vec<Difference>,
+) -> anyhow::Result<()> {
+    values(
+        &format!("{path}.meta"),
+        &serde_json::to_value(&lhs.meta)?,
+        &serde_json::to_value(&rhs.meta)?,
+        differences,
+    );
+
+    let addresses: BTreeSet<_> = lhs.traces.keys().chain(rhs.traces.keys()).collect();
+    for address in addresses {
+        let lhs_trace = lhs.traces.get(address).map(normalized_trace).transpose()?;
+        let rhs_trace = rhs.traces.get(address).map(normalized_trace).transpose()?;
+
+        values(
+            &format!("{path}.traces[{address:?}]"),
+            &lhs_trace.unwrap_or_default(),
+            &rhs_trace.unwrap_or_default(),
+            differences,
+        );
+    }
+
+    Ok(())
+}
+
+/// Serializes a transaction trace, sorting the storage reads so that they are
+/// compared as a set.
+fn normalized_trace<T: Serialize>(trace: &T) -> anyhow::Result<Value> {
+    let mut value = serde_json::to_value(trace).context("failed to serialize txn trace")?;
+    if let Some(Value::Array(keys)) = value.get_mut("storage_read") {
+        keys.sort_by_key(Value::to_string);
+    }
+    Ok(value)
+}
+
+/// Recursively compares two JSON values, recording a [`Difference`] for every
+/// leaf that differs. Missing object fields compare as `null`.
+fn values(path: &str, lhs: &Value, rhs: &Value, differences: &mut Vec<Difference>) {
+    match (lhs, rhs) {
+        (Value::Object(lhs_fields), Value::Object(rhs_fields)) => {
+            let keys: BTreeSet<_> = lhs_fields.keys().chain(rhs_fields.keys()).collect();
+            for key in keys {
+                values(
+                    &format!("{path}.{key}"),
+                    lhs_fields.get(key).unwrap_or(&Value::Null),
+                    rhs_fields.get(key).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        (Value::Array(lhs_items), Value::Array(rhs_items))
+            if lhs_items.len() == rhs_items.len() =>
+        {
+            for (index, (lhs_item, rhs_item)) in lhs_items.iter().zip(rhs_items).enumerate() {
+                values(&format!("{path}[{index}]"), lhs_item, rhs_item, differences);
+            }
+        }
+        _ if lhs != rhs => differences.push(Difference {
+            path: path.to_string(),
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_reports_nested_differences() {
+        let mut differences = Vec::new();
+        values(
+            "root",
+            &json!({"a": 1, "b": {"c": [1, 2]}, "d": "same"}),
+            &json!({"a": 2, "b": {"c": [1, 3]}, "d": "same", "e": true}),
+            &mut differences,
+        );
+
+        assert_eq!(
+            differences,
+            vec![
+                Difference {
+                    path: "root.a".to_string(),
+                    lhs: json!(1),
+                    rhs: json!(2),
+                },
+                Difference {
+                    path: "root.b.c[1]".to_string(),
+                    lhs: json!(2),
+                    rhs: json!(3),
+                },
+                Difference {
+                    path: "root.e".to_string(),
+                    lhs: Value::Null,
+                    rhs: json!(true),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_ignores_storage_read_order() {
+        let lhs = normalized_trace(&json!({"storage_read": ["0x02", "0x01"]})).unwrap();
+        let rhs = normalized_trace(&json!({"storage_read": ["0x01", "0x02"]})).unwrap();
+
+        let mut differences = Vec::new();
+        values("trace", &lhs, &rhs, &mut differences);
+        assert!(differences.is_empty());
+    }
+
+    #[test]
+    fn it_parses_input_sources() {
+        assert!(matches!(
+            "native:http://localhost:8545"
+                .parse::<InputSource>()
+                .unwrap(),
+            InputSource::Rpc {
+                rpc_type: RpcType::Native,
+                ..
+            }
+        ));
+        assert!(matches!(
+            "./witness.json".parse::<InputSource>().unwrap(),
+            InputSource::File(_)
+        ));
+    }
+}
@@ -9,10 +9,12 @@ use clap::ValueEnum;
 use common::block_interval::BlockInterval;
 use evm_arithmetization::proof::{BlockHashes, BlockMetadata};
 use futures::{StreamExt as _, TryStreamExt as _};
-use prover::ProverInput;
+use prover::{BlockProverInput, ProverInput};
 use trace_decoder::types::{BlockLevelData, OtherBlockData};
 
 mod compat;
+pub mod convert;
+pub mod diff;
 pub mod jerigon;
 pub mod native;
 pub mod retry;
@@ -37,35 +39,67 @@ where
     ProviderT: Provider<TransportT>,
     TransportT: Transport + Clone,
 {
-    // Grab interval checkpoint block state trie
-    let checkpoint_state_trie_root = provider
-        .get_block(checkpoint_block_id, BlockTransactionsKind::Hashes)
-        .await?
-        .context("block does not exist")?
-        .header
-        .state_root;
+    let checkpoint_state_trie_root =
+        checkpoint_state_trie_root(provider, checkpoint_block_id).await?;
 
     let mut block_proofs = Vec::new();
     let mut block_interval = block_interval.into_bounded_stream()?;
 
     while let Some(block_num) = block_interval.next().await {
         let block_id = BlockId::Number(BlockNumberOrTag::Number(block_num));
-        let block_prover_input = match rpc_type {
-            RpcType::Jerigon => {
-                jerigon::block_prover_input(&provider, block_id, checkpoint_state_trie_root).await?
-            }
-            RpcType::Native => {
-                native::block_prover_input(&provider, block_id, checkpoint_state_trie_root).await?
-            }
-        };
-
-        block_proofs.push(block_prover_input);
+        block_proofs.push(
+            block_prover_input(
+                provider,
+                block_id,
+                checkpoint_state_trie_root,
+                rpc_type.clone(),
+            )
+            .await?,
+        );
     }
     Ok(ProverInput {
         blocks: block_proofs,
     })
 }
 
+/// Obtain the state trie root of the interval checkpoint block
+pub async fn checkpoint_state_trie_root<ProviderT, TransportT>(
+    provider: &ProviderT,
+    checkpoint_block_id: BlockId,
+) -> anyhow::Result<B256>
+where
+    ProviderT: Provider<TransportT>,
+    TransportT: Transport + Clone,
+{
+    Ok(provider
+        .get_block(checkpoint_block_id, BlockTransactionsKind::Hashes)
+        .await?
+        .context("block does not exist")?
+        .header
+        .state_root)
+}
+
+/// Obtain the prover input for a single block
+pub async fn block_prover_input<ProviderT, TransportT>(
+    provider: &ProviderT,
+    block_id: BlockId,
+    checkpoint_state_trie_root: B256,
+    rpc_type: RpcType,
+) -> anyhow::Result<BlockProverInput>
+where
+    ProviderT: Provider<TransportT>,
+    TransportT: Transport + Clone,
+{
+    match rpc_type {
+        RpcType::Jerigon => {
+            jerigon::block_prover_input(&provider, block_id, checkpoint_state_trie_root).await
+        }
+        RpcType::Native => {
+            native::block_prover_input(&provider, block_id, checkpoint_state_trie_root).await
+        }
+    }
+}
+
 /// Fetches other block data
 async fn fetch_other_block_data<ProviderT, TransportT>(
     provider: ProviderT,
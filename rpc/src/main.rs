@@ -1,9 +1,16 @@
-use std::io;
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
 
 use alloy::rpc::types::eth::BlockId;
 use clap::{Parser, ValueHint};
-use common::block_interval::BlockInterval;
-use rpc::{retry::build_http_retry_provider, RpcType};
+use common::{format::Format, fs::generate_block_input_file_name};
+use prover::BlockProverInput;
+use rpc::{diff::InputSource, retry::build_http_retry_provider, RpcType};
+use serde::Serialize;
+use tracing::info;
 use tracing_subscriber::{prelude::*, EnvFilter};
 use url::Url;
 
@@ -33,6 +40,52 @@ pub enum Cli {
         /// The maximum number of retries
         #[arg(long, default_value_t = 0)]
         max_retries: u32,
+        /// If provided, write the prover input of each block to its own file
+        /// in this directory as soon as it is fetched, instead of stdout.
+        /// Blocks whose file already exists are skipped, so that interrupted
+        /// fetches can be resumed.
+        #[arg(short = 'o', long, value_hint = ValueHint::DirPath)]
+        output_dir: Option<PathBuf>,
+        /// Write compact instead of pretty-printed JSON. Only applies to plain
+        /// JSON output: CBOR and zstd compressed output is always compact.
+        #[arg(long, default_value_t = false)]
+        compact: bool,
+        #[command(flatten)]
+        format: Format,
+    },
+    /// Convert Jerigon combined witnesses of previously fetched prover input
+    /// into separate state and storage tries
+    Convert {
+        /// The file containing the prover input. If not provided, the input
+        /// is read from stdin.
+        #[arg(short = 'f', long, value_hint = ValueHint::FilePath)]
+        input_file: Option<PathBuf>,
+        #[command(flatten)]
+        format: Format,
+    },
+    /// Compare the prover input of a block obtained from two sources
+    Diff {
+        /// The block to compare.
+        #[arg(short, long)]
+        block_number: u64,
+        /// The left-hand side source: `jerigon:<RPC_URL>`, `native:<RPC_URL>`
+        /// or a file previously written by `fetch`.
+        #[arg(long)]
+        lhs: InputSource,
+        /// The right-hand side source: `jerigon:<RPC_URL>`, `native:<RPC_URL>`
+        /// or a file previously written by `fetch`.
+        #[arg(long)]
+        rhs: InputSource,
+        /// The checkpoint block number. If not provided,
+        /// block before the `block_number` is the checkpoint
+        #[arg(short, long)]
+        checkpoint_block_number: Option<BlockId>,
+        /// Backoff in milliseconds for request retries
+        #[arg(long, default_value_t = 0)]
+        backoff: u64,
+        /// The maximum number of retries
+        #[arg(long, default_value_t = 0)]
+        max_retries: u32,
     },
 }
 
@@ -48,27 +101,170 @@ impl Cli {
                 checkpoint_block_number,
                 backoff,
                 max_retries,
+                output_dir,
+                compact,
+                format,
             } => {
                 let checkpoint_block_number =
                     checkpoint_block_number.unwrap_or((start_block - 1).into());
-                let block_interval = BlockInterval::Range(start_block..end_block + 1);
-
-                // Retrieve prover input from the Erigon node
-                let prover_input = rpc::prover_input(
-                    &build_http_retry_provider(rpc_url, backoff, max_retries),
-                    block_interval,
-                    checkpoint_block_number,
-                    rpc_type,
-                )
-                .await?;
-
-                serde_json::to_writer_pretty(io::stdout(), &prover_input.blocks)?;
+                let provider = build_http_retry_provider(rpc_url, backoff, max_retries);
+                let checkpoint_state_trie_root =
+                    rpc::checkpoint_state_trie_root(&provider, checkpoint_block_number).await?;
+
+                if let Some(output_dir) = output_dir {
+                    fs::create_dir_all(&output_dir)?;
+                    for block_number in start_block..=end_block {
+                        let path = output_dir.join(generate_block_input_file_name(
+                            &None,
+                            block_number,
+                            format.file_extension(),
+                        ));
+                        if path.exists() {
+                            info!("Skipping block {block_number}, {} exists", path.display());
+                            continue;
+                        }
+
+                        let block = rpc::block_prover_input(
+                            &provider,
+                            block_number.into(),
+                            checkpoint_state_trie_root,
+                            rpc_type.clone(),
+                        )
+                        .await?;
+
+                        // Written as a single element array, so that each file is
+                        // valid input for the leader `stdio` command.
+                        write_file_atomically(&path, &[block], compact, format)?;
+                        info!(
+                            "Wrote prover input of block {block_number} to {}",
+                            path.display()
+                        );
+                    }
+                } else if format.is_plain_json() {
+                    // Stream the blocks to stdout as a JSON array as they are fetched.
+                    io::stdout().write_all(b"[")?;
+                    for block_number in start_block..=end_block {
+                        let block = rpc::block_prover_input(
+                            &provider,
+                            block_number.into(),
+                            checkpoint_state_trie_root,
+                            rpc_type.clone(),
+                        )
+                        .await?;
+
+                        let mut stdout = io::stdout().lock();
+                        if block_number != start_block {
+                            stdout.write_all(b",")?;
+                        }
+                        write_value(&mut stdout, &block, compact, format)?;
+                        stdout.flush()?;
+                    }
+                    io::stdout().write_all(b"]")?;
+                } else {
+                    // Other formats are written as a whole once all blocks are fetched.
+                    let mut blocks = Vec::new();
+                    for block_number in start_block..=end_block {
+                        blocks.push(
+                            rpc::block_prover_input(
+                                &provider,
+                                block_number.into(),
+                                checkpoint_state_trie_root,
+                                rpc_type.clone(),
+                            )
+                            .await?,
+                        );
+                    }
+                    format.to_writer(io::stdout().lock(), &blocks)?;
+                }
+            }
+            Self::Convert { input_file, format } => {
+                let blocks: Vec<BlockProverInput> = match input_file {
+                    Some(path) => {
+                        common::format::from_reader(io::BufReader::new(File::open(path)?))?
+                    }
+                    None => common::format::from_reader(io::stdin().lock())?,
+                };
+
+                let blocks = blocks
+                    .into_iter()
+                    .map(rpc::convert::block_prover_input)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                write_value(io::stdout().lock(), &blocks, false, format)?;
+            }
+            Self::Diff {
+                block_number,
+                lhs,
+                rhs,
+                checkpoint_block_number,
+                backoff,
+                max_retries,
+            } => {
+                let checkpoint_block_number =
+                    checkpoint_block_number.unwrap_or((block_number - 1).into());
+
+                let (lhs, rhs) = futures::try_join!(
+                    lhs.block_prover_input(
+                        block_number,
+                        checkpoint_block_number,
+                        backoff,
+                        max_retries
+                    ),
+                    rhs.block_prover_input(
+                        block_number,
+                        checkpoint_block_number,
+                        backoff,
+                        max_retries
+                    ),
+                )?;
+
+                let differences = rpc::diff::block_prover_inputs(&lhs, &rhs)?;
+                info!(
+                    "Found {} difference(s) for block {block_number}",
+                    differences.len()
+                );
+
+                serde_json::to_writer_pretty(io::stdout(), &differences)?;
             }
         }
         Ok(())
     }
 }
 
+/// Serializes the value in the given format. Plain JSON is written compact or
+/// pretty-printed.
+fn write_value<T: Serialize>(
+    writer: impl Write,
+    value: &T,
+    compact: bool,
+    format: Format,
+) -> anyhow::Result<()> {
+    if !format.is_plain_json() {
+        format.to_writer(writer, value)?;
+    } else if compact {
+        serde_json::to_writer(writer, value)?;
+    } else {
+        serde_json::to_writer_pretty(writer, value)?;
+    }
+    Ok(())
+}
+
+/// Writes the value to a temporary file which is then renamed to `path`, so
+/// that an interrupted write never leaves a partial file behind.
+fn write_file_atomically<T: Serialize>(
+    path: &Path,
+    value: &T,
+    compact: bool,
+    format: Format,
+) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = io::BufWriter::new(File::create(&tmp_path)?);
+    write_value(&mut writer, value, compact, format)?;
+    writer.into_inner()?.sync_all()?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::Registry::default()
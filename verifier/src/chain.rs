@@ -0,0 +1,165 @@
+//! Checks the public values of block proofs against the canonical chain.
+use alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    rpc::types::eth::{BlockTransactionsKind, Header},
+    transports::Transport,
+};
+use anyhow::Context as _;
+use evm_arithmetization::proof::PublicValues;
+use serde::Serialize;
+
+/// A public value of a proof which differs from the canonical header.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct FieldMismatch {
+    pub(crate) field: &'static str,
+    pub(crate) proof: String,
+    pub(crate) chain: String,
+}
+
+/// Fetches the canonical header of the proven block and reports the public
+/// values which differ from it.
+pub(crate) async fn check_header<ProviderT, TransportT>(
+    provider: &ProviderT,
+    public_values: &PublicValues,
+) -> anyhow::Result<Vec<FieldMismatch>>
+where
+    ProviderT: Provider<TransportT>,
+    TransportT: Transport + Clone,
+{
+    let block_number = public_values.block_metadata.block_number.low_u64();
+    let block = provider
+        .get_block(block_number.into(), BlockTransactionsKind::Hashes)
+        .await?
+        .with_context(|| format!("block {block_number} does not exist"))?;
+
+    Ok(header_mismatches(public_values, &block.header))
+}
+
+/// Compares the block number, hash, roots, gas used, beneficiary and timestamp
+/// of the public values with those of the header.
+fn header_mismatches(public_values: &PublicValues, header: &Header) -> Vec<FieldMismatch> {
+    let optional = |value: Option<String>| value.unwrap_or("none".to_string());
+    let metadata = &public_values.block_metadata;
+    let roots = &public_values.trie_roots_after;
+
+    [
+        (
+            "number",
+            metadata.block_number.to_string(),
+            optional(header.number.map(|number| number.to_string())),
+        ),
+        (
+            "hash",
+            B256::from(public_values.block_hashes.cur_hash.0).to_string(),
+            optional(header.hash.map(|hash| hash.to_string())),
+        ),
+        (
+            "state_root",
+            B256::from(roots.state_root.0).to_string(),
+            header.state_root.to_string(),
+        ),
+        (
+            "transactions_root",
+            B256::from(roots.transactions_root.0).to_string(),
+            header.transactions_root.to_string(),
+        ),
+        (
+            "receipts_root",
+            B256::from(roots.receipts_root.0).to_string(),
+            header.receipts_root.to_string(),
+        ),
+        (
+            "gas_used",
+            metadata.block_gas_used.to_string(),
+            header.gas_used.to_string(),
+        ),
+        (
+            "beneficiary",
+            Address::from(metadata.block_beneficiary.0).to_string(),
+            header.miner.to_string(),
+        ),
+        (
+            "timestamp",
+            metadata.block_timestamp.to_string(),
+            header.timestamp.to_string(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, proof, chain)| proof != chain)
+    .map(|(field, proof, chain)| FieldMismatch {
+        field,
+        proof,
+        chain,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn public_values() -> PublicValues {
+        let mut public_values = PublicValues::default();
+        public_values.block_metadata.block_number = 19240705u64.into();
+        public_values.block_metadata.block_gas_used = 21000u64.into();
+        public_values.block_metadata.block_timestamp = 1708600000u64.into();
+        public_values.block_metadata.block_beneficiary = [2; 20].into();
+        public_values.block_hashes.cur_hash = [3; 32].into();
+        public_values.trie_roots_after.state_root = [4; 32].into();
+        public_values.trie_roots_after.transactions_root = [5; 32].into();
+        public_values.trie_roots_after.receipts_root = [6; 32].into();
+        public_values
+    }
+
+    /// The header of the block proven by `public_values()`.
+    fn header() -> Header {
+        Header {
+            number: Some(19240705),
+            hash: Some(B256::repeat_byte(3)),
+            miner: Address::repeat_byte(2),
+            state_root: B256::repeat_byte(4),
+            transactions_root: B256::repeat_byte(5),
+            receipts_root: B256::repeat_byte(6),
+            gas_used: 21000,
+            timestamp: 1708600000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_accepts_the_proven_header() {
+        assert_eq!(header_mismatches(&public_values(), &header()), vec![]);
+    }
+
+    #[test]
+    fn it_reports_mismatching_fields() {
+        let header = Header {
+            hash: None,
+            state_root: B256::repeat_byte(1),
+            gas_used: 42000,
+            ..header()
+        };
+
+        assert_eq!(
+            header_mismatches(&public_values(), &header),
+            vec![
+                FieldMismatch {
+                    field: "hash",
+                    proof: B256::repeat_byte(3).to_string(),
+                    chain: "none".to_string(),
+                },
+                FieldMismatch {
+                    field: "state_root",
+                    proof: B256::repeat_byte(4).to_string(),
+                    chain: B256::repeat_byte(1).to_string(),
+                },
+                FieldMismatch {
+                    field: "gas_used",
+                    proof: "21000".to_string(),
+                    chain: "42000".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,15 +1,79 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueHint};
+use alloy::{primitives::B256, transports::http::reqwest::Url};
+use clap::{Parser, Subcommand, ValueHint};
 use common::prover_state::cli::CliProverStateConfig;
 
+use crate::dump::DumpFormat;
+
 #[derive(Parser)]
+#[command(subcommand_negates_reqs = true)]
 pub(crate) struct Cli {
-    /// The file containing the proof to verify
-    #[arg(short, long, value_hint = ValueHint::FilePath)]
-    pub(crate) file_path: PathBuf,
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+    /// The file containing the proofs to verify, a directory containing
+    /// `b*.zkproof` files, or a glob pattern of proof files
+    #[arg(short, long, value_hint = ValueHint::FilePath, required = true)]
+    pub(crate) file_path: Option<PathBuf>,
+    /// Additionally check that consecutive proofs form a chain: block numbers
+    /// increase by one, and each proof starts from the state root and block
+    /// hashes of the previous one.
+    #[arg(long, default_value_t = false)]
+    pub(crate) check_continuity: bool,
+    /// The state trie root of the checkpoint block the first proof must start
+    /// from. Implies `--check-continuity`. In any case, all proofs must share
+    /// the checkpoint of the first one.
+    #[arg(long)]
+    pub(crate) checkpoint_state_trie_root: Option<B256>,
+    /// If provided, additionally check the public values of each proof
+    /// against the canonical header of the proven block fetched from this
+    /// RPC node.
+    #[arg(long, short = 'u', value_hint = ValueHint::Url)]
+    pub(crate) rpc_url: Option<Url>,
+    /// Backoff in milliseconds for request retries
+    #[arg(long, default_value_t = 0)]
+    pub(crate) backoff: u64,
+    /// The maximum number of retries
+    #[arg(long, default_value_t = 0)]
+    pub(crate) max_retries: u32,
+    /// Load the verifier from a file written by `export-verifier` instead of
+    /// from the preprocessed circuits. The prover configuration is then
+    /// ignored.
+    #[arg(long, value_hint = ValueHint::FilePath, env = "ZERO_BIN_VERIFIER_DATA")]
+    pub(crate) verifier_data: Option<PathBuf>,
+    /// Write the JSON verification report to this file instead of stdout.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub(crate) report_file: Option<PathBuf>,
     /// The prover configuration used to generate the preprocessed circuits
     /// and the verifier state.
     #[clap(flatten)]
     pub(crate) prover_state_config: CliProverStateConfig,
 }
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Load the verifier state once and verify proofs submitted over HTTP.
+    Serve {
+        /// The port on which to listen.
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Export the final verifier data to a standalone file, allowing hosts to
+    /// verify proofs with `--verifier-data` without the prover configuration
+    /// or circuit generation. Prints the recorded digest.
+    ExportVerifier {
+        /// The file to write the verifier data to.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+    /// Print the public values attested to by proofs, without verifying them.
+    Dump {
+        /// The file containing the proofs, a directory containing
+        /// `b*.zkproof` files, or a glob pattern of proof files
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        file_path: PathBuf,
+        /// How to print the public values.
+        #[arg(long, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+    },
+}
@@ -0,0 +1,232 @@
+//! Checks that a sequence of block proofs forms a chain.
+use alloy::primitives::B256;
+use evm_arithmetization::proof::PublicValues;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub(crate) enum ContinuityError {
+    #[error("block {block} does not follow block {previous}")]
+    NonConsecutive { previous: u64, block: u64 },
+    #[error(
+        "block {block} starts from state root {actual}, but block {previous} ends with {expected}"
+    )]
+    StateRoot {
+        previous: u64,
+        block: u64,
+        expected: B256,
+        actual: B256,
+    },
+    #[error("block {block} has parent hash {actual}, but block {previous} has hash {expected}")]
+    ParentHash {
+        previous: u64,
+        block: u64,
+        expected: B256,
+        actual: B256,
+    },
+    #[error("the previous block hashes of block {block} do not extend those of block {previous}")]
+    PrevHashes { previous: u64, block: u64 },
+    #[error("block {block} was proven from checkpoint state root {actual}, expected {expected}")]
+    Checkpoint {
+        block: u64,
+        expected: B256,
+        actual: B256,
+    },
+    #[error("block {block} starts from state root {actual}, but the checkpoint state root is {expected}")]
+    Start {
+        block: u64,
+        expected: B256,
+        actual: B256,
+    },
+}
+
+/// Checks that the public values of consecutive block proofs link up.
+///
+/// Block numbers must increase by one, each block must start from the state
+/// root the previous block ended with, and its previous block hashes must be
+/// those of the previous block shifted by its hash. All proofs must be proven
+/// from the same checkpoint as the first proof and, if a checkpoint state trie
+/// root is given, the first proof must start from that state.
+pub(crate) fn check(
+    public_values: &[PublicValues],
+    checkpoint_state_trie_root: Option<B256>,
+) -> Vec<ContinuityError> {
+    let mut errors = Vec::new();
+
+    if let Some(first) = public_values.first() {
+        if let Some(expected) = checkpoint_state_trie_root {
+            let actual = B256::from(first.trie_roots_before.state_root.0);
+            if actual != expected {
+                errors.push(ContinuityError::Start {
+                    block: block_number(first),
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        let expected = B256::from(first.extra_block_data.checkpoint_state_trie_root.0);
+        for current in public_values {
+            let actual = B256::from(current.extra_block_data.checkpoint_state_trie_root.0);
+            if actual != expected {
+                errors.push(ContinuityError::Checkpoint {
+                    block: block_number(current),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    for (previous, current) in public_values.iter().zip(public_values.iter().skip(1)) {
+        let (previous_number, block) = (block_number(previous), block_number(current));
+
+        if previous_number.checked_add(1) != Some(block) {
+            errors.push(ContinuityError::NonConsecutive {
+                previous: previous_number,
+                block,
+            });
+        }
+
+        let (expected, actual) = (
+            B256::from(previous.trie_roots_after.state_root.0),
+            B256::from(current.trie_roots_before.state_root.0),
+        );
+        if actual != expected {
+            errors.push(ContinuityError::StateRoot {
+                previous: previous_number,
+                block,
+                expected,
+                actual,
+            });
+        }
+
+        let previous_hashes = &previous.block_hashes.prev_hashes;
+        match current.block_hashes.prev_hashes.split_last() {
+            Some((parent_hash, shifted_hashes)) => {
+                let (expected, actual) = (
+                    B256::from(previous.block_hashes.cur_hash.0),
+                    B256::from(parent_hash.0),
+                );
+                if actual != expected {
+                    errors.push(ContinuityError::ParentHash {
+                        previous: previous_number,
+                        block,
+                        expected,
+                        actual,
+                    });
+                }
+                if previous_hashes.get(1..) != Some(shifted_hashes) {
+                    errors.push(ContinuityError::PrevHashes {
+                        previous: previous_number,
+                        block,
+                    });
+                }
+            }
+            None => errors.push(ContinuityError::PrevHashes {
+                previous: previous_number,
+                block,
+            }),
+        }
+    }
+
+    errors
+}
+
+fn block_number(public_values: &PublicValues) -> u64 {
+    public_values.block_metadata.block_number.low_u64()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A chain of public values for the given blocks, each block ending with
+    /// the state root and hash `[block_number; 32]` (modulo 256).
+    fn chain(block_numbers: std::ops::Range<u8>) -> Vec<PublicValues> {
+        block_numbers
+            .map(|number| {
+                let mut public_values = PublicValues::default();
+                public_values.block_metadata.block_number = u64::from(number).into();
+                public_values.trie_roots_before.state_root = [number - 1; 32].into();
+                public_values.trie_roots_after.state_root = [number; 32].into();
+                public_values.block_hashes.prev_hashes = (0..256)
+                    .map(|i| [(usize::from(number) + i) as u8; 32].into())
+                    .collect();
+                public_values.block_hashes.cur_hash = [number; 32].into();
+                public_values
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_accepts_a_chain() {
+        assert_eq!(check(&chain(1..5), Some(B256::ZERO)), vec![]);
+    }
+
+    #[test]
+    fn it_reports_other_checkpoints() {
+        let mut public_values = chain(1..3);
+        public_values[1].extra_block_data.checkpoint_state_trie_root = [1; 32].into();
+
+        assert_eq!(
+            check(&public_values, None),
+            vec![ContinuityError::Checkpoint {
+                block: 2,
+                expected: B256::ZERO,
+                actual: B256::repeat_byte(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_reports_a_chain_not_starting_from_the_checkpoint() {
+        // The proofs claim the given checkpoint, but the first one does not
+        // start from its state.
+        let mut public_values = chain(2..4);
+        for current in &mut public_values {
+            current.extra_block_data.checkpoint_state_trie_root = [0; 32].into();
+        }
+
+        assert_eq!(
+            check(&public_values, Some(B256::ZERO)),
+            vec![ContinuityError::Start {
+                block: 2,
+                expected: B256::ZERO,
+                actual: B256::repeat_byte(1),
+            }]
+        );
+        assert_eq!(check(&public_values, Some(B256::repeat_byte(1))), vec![]);
+    }
+
+    #[test]
+    fn it_reports_gaps() {
+        let mut public_values = chain(1..5);
+        public_values.remove(2);
+
+        assert_eq!(
+            check(&public_values, None),
+            vec![
+                ContinuityError::NonConsecutive {
+                    previous: 2,
+                    block: 4,
+                },
+                ContinuityError::StateRoot {
+                    previous: 2,
+                    block: 4,
+                    expected: B256::repeat_byte(2),
+                    actual: B256::repeat_byte(3),
+                },
+                ContinuityError::ParentHash {
+                    previous: 2,
+                    block: 4,
+                    expected: B256::repeat_byte(2),
+                    actual: B256::repeat_byte(3),
+                },
+                ContinuityError::PrevHashes {
+                    previous: 2,
+                    block: 4,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Printing of the public values attested to by block proofs.
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use evm_arithmetization::proof::{PublicValues, TrieRoots};
+use serde::Serialize;
+
+/// How to print the public values.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum DumpFormat {
+    /// Pretty-printed JSON.
+    #[default]
+    Json,
+    /// Human readable text.
+    Text,
+}
+
+impl std::fmt::Display for DumpFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DumpFormat::Json => write!(f, "json"),
+            DumpFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// The public values of a block proof.
+#[derive(Serialize)]
+pub(crate) struct ProofPublicValues {
+    /// The block height recorded alongside the proof.
+    pub(crate) block_height: u64,
+    /// The public values, `None` if they could not be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) public_values: Option<PublicValues>,
+    /// Why the public values could not be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+/// Writes the public values of the given proofs in the given format.
+pub(crate) fn dump(
+    mut writer: impl Write,
+    proofs: &[ProofPublicValues],
+    format: DumpFormat,
+) -> Result<()> {
+    match format {
+        DumpFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, proofs)?;
+            writeln!(writer)?;
+        }
+        DumpFormat::Text => {
+            for proof in proofs {
+                write_text(&mut writer, proof)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_text(mut w: impl Write, proof: &ProofPublicValues) -> Result<()> {
+    let ProofPublicValues {
+        block_height,
+        public_values,
+        error,
+    } = proof;
+    writeln!(w, "Proof of block {block_height}")?;
+    let Some(public_values) = public_values else {
+        let error = error.as_deref().unwrap_or("unknown error");
+        writeln!(w, "  Public values could not be decoded: {error}")?;
+        return Ok(());
+    };
+    let metadata = &public_values.block_metadata;
+    let hashes = &public_values.block_hashes;
+    let extra = &public_values.extra_block_data;
+
+    write_trie_roots(
+        &mut w,
+        "Trie roots before",
+        &public_values.trie_roots_before,
+    )?;
+    write_trie_roots(&mut w, "Trie roots after", &public_values.trie_roots_after)?;
+
+    writeln!(w, "  Block metadata")?;
+    writeln!(w, "    number:           {}", metadata.block_number)?;
+    writeln!(w, "    timestamp:        {}", metadata.block_timestamp)?;
+    writeln!(w, "    beneficiary:      {:?}", metadata.block_beneficiary)?;
+    writeln!(w, "    chain id:         {}", metadata.block_chain_id)?;
+    writeln!(w, "    difficulty:       {}", metadata.block_difficulty)?;
+    writeln!(w, "    random:           {:?}", metadata.block_random)?;
+    writeln!(w, "    gas limit:        {}", metadata.block_gaslimit)?;
+    writeln!(w, "    gas used:         {}", metadata.block_gas_used)?;
+    writeln!(w, "    base fee:         {}", metadata.block_base_fee)?;
+
+    writeln!(w, "  Block hashes")?;
+    writeln!(w, "    current:          {:?}", hashes.cur_hash)?;
+    for (index, hash) in hashes.prev_hashes.iter().enumerate() {
+        writeln!(w, "    previous[{index:>3}]:    {hash:?}")?;
+    }
+
+    writeln!(w, "  Extra block data")?;
+    writeln!(
+        w,
+        "    checkpoint root:  {:?}",
+        extra.checkpoint_state_trie_root
+    )?;
+    writeln!(
+        w,
+        "    txn number:       {} -> {}",
+        extra.txn_number_before, extra.txn_number_after
+    )?;
+    writeln!(
+        w,
+        "    gas used:         {} -> {}",
+        extra.gas_used_before, extra.gas_used_after
+    )?;
+    Ok(())
+}
+
+fn write_trie_roots(mut w: impl Write, title: &str, roots: &TrieRoots) -> Result<()> {
+    writeln!(w, "  {title}")?;
+    writeln!(w, "    state:            {:?}", roots.state_root)?;
+    writeln!(w, "    transactions:     {:?}", roots.transactions_root)?;
+    writeln!(w, "    receipts:         {:?}", roots.receipts_root)?;
+    Ok(())
+}
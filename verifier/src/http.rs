@@ -0,0 +1,57 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{body::Bytes, http::StatusCode, routing::post, Json, Router};
+use common::proof::BlockProofFile;
+use tracing::{debug, error, info};
+
+use crate::{report::ProofReport, Verifier};
+
+/// The main function for the HTTP mode.
+pub(crate) async fn http_main(verifier: Verifier, port: u16) -> anyhow::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    debug!("listening on {}", addr);
+
+    let verifier = Arc::new(verifier);
+    let app = Router::new().route("/verify", post(move |body| verify(body, verifier.clone())));
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    Ok(axum::serve(listener, app).await?)
+}
+
+/// Verifies a single block proof, in any format readable by
+/// [`common::format::from_slice`].
+///
+/// Responds with the [`ProofReport`] of the proof, or `400 Bad Request` if the
+/// body is not a block proof.
+async fn verify(
+    body: Bytes,
+    verifier: Arc<Verifier>,
+) -> Result<Json<ProofReport>, (StatusCode, String)> {
+    let block_proof: BlockProofFile = common::format::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid proof: {e}")))?;
+
+    // Verification is CPU bound, keep it off the async workers.
+    let report = tokio::task::spawn_blocking(move || {
+        let result = verifier.verify(&block_proof);
+        let public_values = common::proof::public_values(block_proof.proof());
+        ProofReport {
+            block_height: block_proof.proof().b_height,
+            verified: result.is_ok(),
+            error: result.err().map(|e| format!("{e:#}")),
+            public_values: public_values.as_ref().ok().map(Into::into),
+            public_values_error: public_values.err().map(|e| e.to_string()),
+            chain_mismatches: None,
+            chain_error: None,
+        }
+    })
+    .await
+    .map_err(|e| {
+        error!("Verification task failed: {e}");
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+
+    info!(
+        "Proof of block {} verified: {}",
+        report.block_height, report.verified
+    );
+    Ok(Json(report))
+}
@@ -0,0 +1,110 @@
+//! Resolution and loading of the proof files to verify.
+use std::{
+    fmt,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _, Result};
+use common::proof::BlockProofFile;
+use rayon::prelude::*;
+use serde::{
+    de::{value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// The pattern of the proof files written by the leader to a directory.
+const PROOF_FILE_PATTERN: &str = "b*.zkproof";
+
+/// The contents of a proof file: a single proof, as written by the leader to a
+/// directory, or an array of proofs, as written to stdout.
+enum ProofFileContents {
+    Single(Box<BlockProofFile>),
+    Many(Vec<BlockProofFile>),
+}
+
+impl<'de> Deserialize<'de> for ProofFileContents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ProofFileContentsVisitor)
+    }
+}
+
+/// Tells a single proof and an array of proofs apart by their shape, such
+/// that the proofs are deserialized once and errors point at the invalid
+/// field.
+struct ProofFileContentsVisitor;
+
+impl<'de> Visitor<'de> for ProofFileContentsVisitor {
+    type Value = ProofFileContents;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a block proof or an array of block proofs")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        BlockProofFile::deserialize(MapAccessDeserializer::new(map))
+            .map(|proof| ProofFileContents::Single(Box::new(proof)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut proofs = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(proof) = seq.next_element()? {
+            proofs.push(proof);
+        }
+        Ok(ProofFileContents::Many(proofs))
+    }
+}
+
+/// Resolves `path` to the proof files it names: the file itself, the
+/// `b*.zkproof` files of a directory, or the files matching a glob pattern.
+pub(crate) fn proof_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let pattern = if path.is_dir() {
+        path.join(PROOF_FILE_PATTERN)
+    } else {
+        path.to_path_buf()
+    };
+    let pattern = pattern
+        .to_str()
+        .with_context(|| format!("invalid path {}", pattern.display()))?;
+
+    let files = glob::glob(pattern)
+        .with_context(|| format!("invalid glob pattern {pattern}"))?
+        .filter_map(|entry| entry.map(|path| path.is_file().then_some(path)).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+    if files.is_empty() {
+        bail!("no proof files found at {}", path.display());
+    }
+    Ok(files)
+}
+
+/// Reads the proofs of all files in parallel.
+///
+/// Proofs read from several files are ordered by block height, proofs of a
+/// single file keep their order.
+pub(crate) fn read_proofs(files: &[PathBuf]) -> Result<Vec<BlockProofFile>> {
+    let contents = files
+        .par_iter()
+        .map(|path| {
+            let file = File::open(path)?;
+            common::format::from_reader(BufReader::new(file))
+                .with_context(|| format!("failed to read proofs from {}", path.display()))
+        })
+        .collect::<Result<Vec<ProofFileContents>>>()?;
+
+    let mut proofs: Vec<_> = contents
+        .into_iter()
+        .flat_map(|contents| match contents {
+            ProofFileContents::Single(proof) => vec![*proof],
+            ProofFileContents::Many(proofs) => proofs,
+        })
+        .collect();
+    if files.len() > 1 {
+        proofs.sort_by_key(|proof| proof.proof().b_height);
+    }
+    Ok(proofs)
+}
@@ -1,39 +1,328 @@
-use std::fs::File;
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context as _, Result};
 use clap::Parser;
+use cli::Command;
+use common::{
+    proof::{
+        known_evm_arithmetization_pkg_ver, BlockProofFile, ProofMetadataError, UNKNOWN_PKG_VER,
+    },
+    prover_state::{cli::CliProverStateConfig, verifier_data, ProverStateManager},
+};
 use dotenvy::dotenv;
-use proof_gen::proof_types::GeneratedBlockProof;
-use serde_json::Deserializer;
-use tracing::info;
+use proof_gen::VerifierState;
+use rayon::prelude::*;
+use report::{ProofReport, Report};
+use tracing::{error, info};
 
+mod chain;
 mod cli;
+mod continuity;
+mod dump;
+mod http;
 mod init;
+mod input;
+mod report;
 
-fn main() -> Result<()> {
+/// The circuits a verifier verifies proofs of.
+struct VerifierCircuits {
+    circuit_config_digest: String,
+    evm_arithmetization_pkg_ver: Option<String>,
+}
+
+impl VerifierCircuits {
+    /// Checks that the proof, if it records its circuits, was generated with
+    /// these circuits.
+    fn check(&self, block_proof: &BlockProofFile) -> Result<(), ProofMetadataError> {
+        match block_proof.metadata() {
+            Some(metadata) => metadata.check_circuit_digest(
+                &self.circuit_config_digest,
+                self.evm_arithmetization_pkg_ver.as_deref(),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Where the verifier state is loaded from.
+enum VerifierSource {
+    /// An exported verifier data file.
+    VerifierData(PathBuf),
+    /// The circuits of the prover state configuration.
+    ProverState(ProverStateManager),
+}
+
+impl VerifierSource {
+    /// Returns the source of the verifier, from the given exported verifier
+    /// data file or, if none is given, from the prover state configuration,
+    /// and the circuits it verifies proofs of, without loading any circuits.
+    fn new(
+        verifier_data: Option<&Path>,
+        prover_state_config: CliProverStateConfig,
+    ) -> Result<(Self, VerifierCircuits)> {
+        match verifier_data {
+            Some(path) => {
+                let header = verifier_data::read_header(path)?;
+                let circuits = VerifierCircuits {
+                    circuit_config_digest: header.circuit_config_digest,
+                    evm_arithmetization_pkg_ver: Some(header.evm_arithmetization_pkg_ver)
+                        .filter(|pkg_ver| pkg_ver != UNKNOWN_PKG_VER),
+                };
+                Ok((Self::VerifierData(path.to_path_buf()), circuits))
+            }
+            None => {
+                let prover_state_manager = prover_state_config.into_prover_state_manager()?;
+                let circuits = VerifierCircuits {
+                    circuit_config_digest: prover_state_manager
+                        .circuit_config
+                        .get_configuration_digest(),
+                    evm_arithmetization_pkg_ver: known_evm_arithmetization_pkg_ver(),
+                };
+                Ok((Self::ProverState(prover_state_manager), circuits))
+            }
+        }
+    }
+
+    /// Loads the verifier state, loading or generating the circuits if needed.
+    fn load(self, circuits: VerifierCircuits) -> Result<Verifier> {
+        let state = match self {
+            Self::VerifierData(path) => {
+                let (header, state) = verifier_data::import(&path)?;
+                info!(
+                    "Loaded verifier data {} for circuits {}",
+                    header.digest, header.circuit_config_digest
+                );
+                state
+            }
+            Self::ProverState(prover_state_manager) => prover_state_manager.verifier()?,
+        };
+        Ok(Verifier { state, circuits })
+    }
+}
+
+/// A verifier state together with the circuits it verifies proofs of.
+pub(crate) struct Verifier {
+    state: VerifierState,
+    circuits: VerifierCircuits,
+}
+
+impl Verifier {
+    /// Loads the verifier from the given exported verifier data file or, if
+    /// none is given, from the prover state configuration.
+    fn load(
+        verifier_data: Option<&Path>,
+        prover_state_config: CliProverStateConfig,
+    ) -> Result<Self> {
+        let (source, circuits) = VerifierSource::new(verifier_data, prover_state_config)?;
+        source.load(circuits)
+    }
+
+    /// Verifies a block proof, rejecting proofs generated with other circuits
+    /// without attempting verification.
+    pub(crate) fn verify(&self, block_proof: &BlockProofFile) -> Result<()> {
+        self.circuits.check(block_proof)?;
+        self.state.verify(&block_proof.proof().intern)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
     dotenv().ok();
     init::tracing();
 
     let args = cli::Cli::parse();
-    let file = File::open(args.file_path)?;
-    let des = &mut Deserializer::from_reader(&file);
-    let input_proofs: Vec<GeneratedBlockProof> = serde_path_to_error::deserialize(des)?;
-
-    let verifer = args
-        .prover_state_config
-        .into_prover_state_manager()
-        .verifier()?;
-
-    if input_proofs.into_iter().all(|block_proof| {
-        verifer
-            .verify(&block_proof.intern)
-            .map_err(|e| {
-                info!("Proof verification failed with error: {:?}", e);
-            })
-            .is_ok()
-    }) {
-        info!("All proofs verified successfully!");
+    match args.command {
+        Some(Command::Serve { port }) => {
+            let verifier = Verifier::load(args.verifier_data.as_deref(), args.prover_state_config)?;
+            http::http_main(verifier, port).await
+        }
+        Some(Command::ExportVerifier { output }) => {
+            let prover_state_manager = args.prover_state_config.into_prover_state_manager()?;
+            let verifier = prover_state_manager.verifier()?;
+            let header = verifier_data::export(
+                &output,
+                &verifier.state,
+                &prover_state_manager.circuit_config,
+            )?;
+            info!(
+                "Exported verifier data {} to {}",
+                header.digest,
+                output.display()
+            );
+            serde_json::to_writer_pretty(io::stdout().lock(), &header)?;
+            Ok(())
+        }
+        Some(Command::Dump { file_path, format }) => {
+            let proofs: Vec<_> = input::read_proofs(&input::proof_files(&file_path)?)?
+                .iter()
+                .map(|block_proof| {
+                    let block_height = block_proof.proof().b_height;
+                    let public_values = common::proof::public_values(block_proof.proof());
+                    if let Err(e) = &public_values {
+                        error!("Failed to decode the public values of block {block_height}: {e}");
+                    }
+                    dump::ProofPublicValues {
+                        block_height,
+                        error: public_values.as_ref().err().map(ToString::to_string),
+                        public_values: public_values.ok(),
+                    }
+                })
+                .collect();
+            dump::dump(io::stdout().lock(), &proofs, format)?;
+
+            let undecoded = proofs.iter().filter(|proof| proof.error.is_some()).count();
+            if undecoded > 0 {
+                bail!("the public values of {undecoded} proof(s) could not be decoded");
+            }
+            Ok(())
+        }
+        None => {
+            let file_path = args.file_path.clone().context("a proof file is required")?;
+            verify_file(args, file_path).await
+        }
+    }
+}
+
+/// Verifies all proofs in the given file, directory or glob pattern in
+/// parallel and writes the [`Report`].
+async fn verify_file(args: cli::Cli, file_path: PathBuf) -> Result<()> {
+    let files = input::proof_files(&file_path)?;
+    let input_proofs = input::read_proofs(&files)?;
+    info!(
+        "Verifying {} proof(s) from {} file(s)",
+        input_proofs.len(),
+        files.len()
+    );
+
+    // Proofs generated with other circuits are rejected before loading the
+    // circuits, which are not loaded at all if no proof can be verified.
+    let (source, circuits) =
+        VerifierSource::new(args.verifier_data.as_deref(), args.prover_state_config)?;
+    let checks: Vec<_> = input_proofs
+        .iter()
+        .map(|block_proof| circuits.check(block_proof))
+        .collect();
+    let verifier = if checks.iter().any(Result::is_ok) {
+        Some(source.load(circuits)?)
+    } else {
+        info!("No proof was generated with the configured circuits, skipping loading them");
+        None
     };
 
+    let public_values: Vec<_> = input_proofs
+        .iter()
+        .map(|block_proof| common::proof::public_values(block_proof.proof()))
+        .collect();
+
+    let provider = args.rpc_url.map(|rpc_url| {
+        rpc::retry::build_http_retry_provider(rpc_url, args.backoff, args.max_retries)
+    });
+
+    let verified = AtomicUsize::new(0);
+    let results: Vec<_> = input_proofs
+        .par_iter()
+        .zip(checks)
+        .map(|(block_proof, check)| {
+            check?;
+            let verifier = verifier
+                .as_ref()
+                .expect("the verifier is loaded if any proof matches its circuits");
+            let result = verifier.state.verify(&block_proof.proof().intern);
+            let verified = verified.fetch_add(1, Ordering::Relaxed) + 1;
+            info!("Verified {verified}/{} proof(s)", input_proofs.len());
+            result
+        })
+        .collect();
+
+    let mut report = Report::default();
+    for ((block_proof, public_values), result) in
+        input_proofs.iter().zip(&public_values).zip(results)
+    {
+        let block_height = block_proof.proof().b_height;
+        if let Err(e) = &result {
+            error!("Proof verification of block {block_height} failed: {e:#}");
+        }
+
+        if let Err(e) = public_values {
+            error!("Failed to decode the public values of block {block_height}: {e}");
+        }
+
+        let (chain_mismatches, chain_error) = match (&provider, public_values) {
+            (Some(provider), Ok(public_values)) => {
+                match chain::check_header(provider, public_values).await {
+                    Ok(mismatches) => {
+                        for mismatch in &mismatches {
+                            error!(
+                                "Block {block_height} {} mismatch: proof has {}, chain has {}",
+                                mismatch.field, mismatch.proof, mismatch.chain
+                            );
+                        }
+                        (Some(mismatches), None)
+                    }
+                    Err(e) => {
+                        error!("Header of block {block_height} not checked: {e:#}");
+                        (None, Some(format!("header not checked: {e:#}")))
+                    }
+                }
+            }
+            _ => (None, None),
+        };
+
+        report.proofs.push(ProofReport {
+            block_height,
+            verified: result.is_ok(),
+            error: result.err().map(|e| format!("{e:#}")),
+            public_values: public_values.as_ref().ok().map(Into::into),
+            public_values_error: public_values.as_ref().err().map(ToString::to_string),
+            chain_mismatches,
+            chain_error,
+        });
+    }
+
+    if args.check_continuity || args.checkpoint_state_trie_root.is_some() {
+        // Proofs whose public values could not be decoded break the chain.
+        let public_values: Vec<_> = public_values.into_iter().filter_map(Result::ok).collect();
+        let errors = continuity::check(&public_values, args.checkpoint_state_trie_root);
+        for e in &errors {
+            error!("Continuity check failed: {e}");
+        }
+        report.continuity_errors = Some(errors.iter().map(ToString::to_string).collect());
+    }
+
+    let failed_proofs = report.proofs.iter().filter(|proof| !proof.passed()).count();
+    let failed_continuity_checks = report.continuity_errors.as_ref().map_or(0, Vec::len);
+    report.passed = report.proofs.len() - failed_proofs;
+    report.failed = failed_proofs;
+    report.success = failed_proofs == 0 && failed_continuity_checks == 0;
+    info!(
+        "{} proof(s) passed, {failed_proofs} failed, {failed_continuity_checks} continuity check(s) failed",
+        report.passed
+    );
+
+    match args.report_file {
+        Some(path) => serde_json::to_writer_pretty(File::create(path)?, &report)?,
+        None => {
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &report)?;
+            stdout.write_all(b"\n")?;
+        }
+    }
+
+    if failed_proofs > 0 {
+        bail!(
+            "{failed_proofs} of {} proof(s) failed verification or differ from the chain",
+            report.proofs.len()
+        );
+    }
+    if failed_continuity_checks > 0 {
+        bail!("{failed_continuity_checks} continuity check(s) failed");
+    }
+    info!("All proofs verified successfully!");
+
     Ok(())
 }
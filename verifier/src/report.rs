@@ -0,0 +1,96 @@
+//! Machine-readable verification results.
+use alloy::primitives::B256;
+use evm_arithmetization::proof::PublicValues;
+use serde::Serialize;
+
+use crate::chain::FieldMismatch;
+
+/// The outcome of verifying a sequence of block proofs.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Report {
+    /// Whether every proof verified and, if checked, matches the chain and
+    /// the proofs form a chain.
+    pub(crate) success: bool,
+    /// The number of proofs which passed.
+    pub(crate) passed: usize,
+    /// The number of proofs which failed.
+    pub(crate) failed: usize,
+    /// The outcome of each proof, in input order.
+    pub(crate) proofs: Vec<ProofReport>,
+    /// The continuity check failures, `None` if continuity was not checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) continuity_errors: Option<Vec<String>>,
+}
+
+/// The outcome of verifying a single block proof.
+#[derive(Debug, Serialize)]
+pub(crate) struct ProofReport {
+    /// The block height recorded alongside the proof.
+    pub(crate) block_height: u64,
+    pub(crate) verified: bool,
+    /// Why the proof failed to verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    /// The public values of the proof, `None` if they could not be decoded.
+    pub(crate) public_values: Option<PublicValuesSummary>,
+    /// Why the public values could not be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) public_values_error: Option<String>,
+    /// The public values differing from the canonical header, `None` if not
+    /// checked against the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) chain_mismatches: Option<Vec<FieldMismatch>>,
+    /// Why the public values could not be checked against the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) chain_error: Option<String>,
+}
+
+impl ProofReport {
+    /// Whether the proof verified, its public values were decoded and they
+    /// match the chain, if checked.
+    pub(crate) fn passed(&self) -> bool {
+        self.verified
+            && self.public_values_error.is_none()
+            && self.chain_error.is_none()
+            && self.chain_mismatches.iter().all(Vec::is_empty)
+    }
+}
+
+/// The public values of a block proof most relevant to identify the proven
+/// block.
+#[derive(Debug, Serialize)]
+pub(crate) struct PublicValuesSummary {
+    pub(crate) block_number: u64,
+    pub(crate) block_hash: B256,
+    pub(crate) parent_hash: Option<B256>,
+    pub(crate) block_timestamp: u64,
+    pub(crate) state_root_before: B256,
+    pub(crate) state_root_after: B256,
+    pub(crate) transactions_root: B256,
+    pub(crate) receipts_root: B256,
+    pub(crate) gas_used: u64,
+    pub(crate) checkpoint_state_trie_root: B256,
+}
+
+impl From<&PublicValues> for PublicValuesSummary {
+    fn from(public_values: &PublicValues) -> Self {
+        Self {
+            block_number: public_values.block_metadata.block_number.low_u64(),
+            block_hash: B256::from(public_values.block_hashes.cur_hash.0),
+            parent_hash: public_values
+                .block_hashes
+                .prev_hashes
+                .last()
+                .map(|hash| B256::from(hash.0)),
+            block_timestamp: public_values.block_metadata.block_timestamp.low_u64(),
+            state_root_before: B256::from(public_values.trie_roots_before.state_root.0),
+            state_root_after: B256::from(public_values.trie_roots_after.state_root.0),
+            transactions_root: B256::from(public_values.trie_roots_after.transactions_root.0),
+            receipts_root: B256::from(public_values.trie_roots_after.receipts_root.0),
+            gas_used: public_values.block_metadata.block_gas_used.low_u64(),
+            checkpoint_state_trie_root: B256::from(
+                public_values.extra_block_data.checkpoint_state_trie_root.0,
+            ),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! HTTP endpoints reporting the state of the worker to orchestrators.
+//!
+//! - `GET /health/live`: `200 OK` while the process is running.
+//! - `GET /health/ready`: `200 OK` once the prover state is initialized, `503
+//!   Service Unavailable` while circuits are being loaded or the worker is
+//!   shutting down.
+//! - `GET /status`: the state of the worker, its circuit configuration and the
+//!   operations it is executing, as JSON.
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use common::proof::evm_arithmetization_pkg_ver;
+use ops::tasks::{self, RunningTask};
+use serde::Serialize;
+use tracing::{error, info};
+
+/// The state of the worker shared with the health endpoints.
+pub(crate) struct Health {
+    ready: AtomicBool,
+    draining: AtomicBool,
+    circuit_config_digest: String,
+}
+
+impl Health {
+    pub(crate) fn new(circuit_config_digest: String) -> Self {
+        Self {
+            ready: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            circuit_config_digest,
+        }
+    }
+
+    /// Marks the prover state as initialized.
+    pub(crate) fn set_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Marks the worker as shutting down.
+    pub(crate) fn set_draining(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+}
+
+/// The state of the worker.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum WorkerState {
+    /// The prover state is being initialized, e.g. circuits are being loaded
+    /// or generated.
+    Loading,
+    /// Waiting for tasks.
+    Idle,
+    /// Executing tasks.
+    Busy,
+    /// Shutting down, finishing the tasks being executed.
+    Draining,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    state: WorkerState,
+    /// The digest of the circuit configuration of the prover state.
+    circuit_config_digest: String,
+    evm_arithmetization_pkg_ver: String,
+    /// The operations being executed, oldest first.
+    tasks: Vec<RunningTask>,
+}
+
+/// Binds the health server to `port`, and serves it in the background.
+pub(crate) async fn spawn(port: u16, health: Arc<Health>) -> anyhow::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!("health server listening on {addr}");
+
+    let app = Router::new()
+        .route("/health/live", get(|| async { StatusCode::OK }))
+        .route("/health/ready", get(ready))
+        .route("/status", get(status))
+        .with_state(health);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("health server failed: {e}");
+        }
+    });
+    Ok(())
+}
+
+async fn ready(State(health): State<Arc<Health>>) -> StatusCode {
+    if health.is_ready() && !health.is_draining() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+async fn status(State(health): State<Arc<Health>>) -> Json<Status> {
+    let tasks = tasks::running();
+    let state = match (health.is_ready(), tasks.is_empty()) {
+        _ if health.is_draining() => WorkerState::Draining,
+        (false, _) => WorkerState::Loading,
+        (true, true) => WorkerState::Idle,
+        (true, false) => WorkerState::Busy,
+    };
+    Json(Status {
+        state,
+        circuit_config_digest: health.circuit_config_digest.clone(),
+        evm_arithmetization_pkg_ver: evm_arithmetization_pkg_ver(),
+        tasks,
+    })
+}
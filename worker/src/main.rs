@@ -1,10 +1,17 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use anyhow::Result;
 use clap::Parser;
-use common::prover_state::cli::CliProverStateConfig;
+use common::{prover_state::cli::CliProverStateConfig, shutdown::Shutdown};
 use dotenvy::dotenv;
-use ops::register;
+use ops::{register, tasks};
 use paladin::runtime::WorkerRuntime;
+use tracing::{info, warn};
 
+mod health;
 mod init;
 
 #[derive(Parser, Debug)]
@@ -13,6 +20,37 @@ struct Cli {
     paladin: paladin::config::Config,
     #[clap(flatten)]
     prover_state_config: CliProverStateConfig,
+    /// If provided, serve the health endpoints (`/health/live`,
+    /// `/health/ready` and `/status`) on this port.
+    #[arg(long, env = "ZERO_BIN_HEALTH_PORT")]
+    health_port: Option<u16>,
+    /// On SIGINT or SIGTERM, the maximum time in seconds to wait for the
+    /// running tasks to complete, after which they are requeued.
+    #[arg(long, env = "ZERO_BIN_SHUTDOWN_TIMEOUT", default_value_t = DEFAULT_SHUTDOWN_TIMEOUT_SECS)]
+    shutdown_timeout: u64,
+}
+
+/// The default of [`Cli::shutdown_timeout`].
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 600;
+
+/// The interval at which the running tasks are checked while draining.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the worker must be idle to be considered drained, such that the
+/// result of the last task is sent.
+const DRAIN_IDLE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Resolves once no task has been running for [`DRAIN_IDLE_PERIOD`].
+async fn drained() {
+    let mut idle_since = Instant::now();
+    loop {
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        if !tasks::running().is_empty() {
+            idle_since = Instant::now();
+        } else if idle_since.elapsed() >= DRAIN_IDLE_PERIOD {
+            return;
+        }
+    }
 }
 
 #[tokio::main]
@@ -20,13 +58,59 @@ async fn main() -> Result<()> {
     dotenv().ok();
     init::tracing();
     let args = Cli::parse();
+    let shutdown = Shutdown::on_signal();
 
-    args.prover_state_config
-        .into_prover_state_manager()
-        .initialize()?;
+    let prover_state_manager = args.prover_state_config.into_prover_state_manager()?;
+    let health = Arc::new(health::Health::new(
+        prover_state_manager
+            .circuit_config
+            .get_configuration_digest(),
+    ));
+    if let Some(port) = args.health_port {
+        health::spawn(port, health.clone()).await?;
+    }
+
+    prover_state_manager.initialize()?;
+    if shutdown.is_triggered() {
+        return Ok(());
+    }
+    health.set_ready();
 
     let runtime = WorkerRuntime::from_config(&args.paladin, register()).await?;
-    runtime.main_loop().await?;
+    let main_loop = runtime.main_loop();
+    tokio::pin!(main_loop);
+    tokio::select! {
+        result = &mut main_loop => {
+            result?;
+            return Ok(());
+        }
+        _ = shutdown.wait() => {}
+    }
+
+    // The runtime offers no way to stop consuming tasks, so the tasks received
+    // from now on are held rather than executed, while the main loop keeps
+    // running until the tasks being executed complete and their results are sent.
+    health.set_draining();
+    tasks::hold_new();
+    info!(
+        "Waiting for {} running tasks to complete",
+        tasks::running().len()
+    );
+    let drain_timeout = Duration::from_secs(args.shutdown_timeout);
+    let mut exit_code = 0;
+    tokio::select! {
+        result = &mut main_loop => result?,
+        drained = tokio::time::timeout(drain_timeout, drained()) => {
+            if drained.is_err() {
+                warn!("Shutdown timeout elapsed, requeuing tasks: {:?}", tasks::running());
+                exit_code = 1;
+            }
+        }
+    }
 
-    Ok(())
+    // Returning would wait for the held and abandoned tasks, as the runtime waits
+    // for its blocking threads. Exiting closes the connection to the broker
+    // instead, which requeues the tasks not completed for other workers (see
+    // `tasks::hold_new`). Abandoning running tasks fails the exit status.
+    std::process::exit(exit_code)
 }